@@ -0,0 +1,455 @@
+use libc::c_char;
+use std::borrow::Cow;
+use std::cmp;
+use std::mem;
+
+use super::{is_token_char, trim_start, ChunkedDecoder, ParseError};
+
+// Values of `phr_chunked_decoder::_state`, in the order of the C enum.
+const IN_CHUNK_SIZE: c_char = 0;
+const IN_CHUNK_EXT: c_char = 1;
+const IN_CHUNK_DATA: c_char = 2;
+const IN_CHUNK_CRLF: c_char = 3;
+const IN_TRAILERS_LINE_HEAD: c_char = 4;
+const IN_TRAILERS_LINE_MIDDLE: c_char = 5;
+
+/// Iterator over the chunk data in a chunked body, created by
+/// `ChunkedDecoder::slices`.
+///
+/// Each item points into the encoded buffer, so nothing is copied or moved.
+/// A chunk that is split across buffers is yielded in pieces. Iteration
+/// stops at the end of the body, at the end of the buffer, or after the
+/// first error.
+#[derive(Debug)]
+pub struct ChunkSlices<'d, 'buf> {
+    decoder: &'d mut ChunkedDecoder,
+    buf: &'buf [u8],
+    offset: usize,
+    complete: bool,
+    failed: bool,
+}
+
+impl<'d, 'buf> ChunkSlices<'d, 'buf> {
+    pub(crate) fn new(decoder: &'d mut ChunkedDecoder, buf: &'buf [u8]) -> ChunkSlices<'d, 'buf> {
+        ChunkSlices {
+            decoder,
+            buf,
+            offset: 0,
+            complete: false,
+            failed: false,
+        }
+    }
+
+    /// The number of bytes of the buffer walked so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the end of the body was reached. Once it is, `remainder`
+    /// holds the data that followed the body.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The part of the buffer that has not been walked yet.
+    pub fn remainder(&self) -> &'buf [u8] {
+        &self.buf[self.offset..]
+    }
+
+    // Advances through the buffer until the next piece of chunk data, the
+    // end of the body or the end of the buffer, mirroring
+    // `phr_decode_chunked` state for state.
+    fn next_slice(&mut self) -> Result<Option<&'buf [u8]>, ParseError> {
+        let buf = self.buf;
+        let raw = &mut self.decoder.raw;
+        loop {
+            match raw._state {
+                IN_CHUNK_SIZE => {
+                    loop {
+                        let b = match buf.get(self.offset) {
+                            Some(&b) => b,
+                            None => return Ok(None),
+                        };
+                        let v = match (b as char).to_digit(16) {
+                            Some(v) => v as usize,
+                            None if raw._hex_count == 0 => return Err(ParseError::ChunkedEncoding),
+                            None => break,
+                        };
+                        if raw._hex_count as usize == mem::size_of::<usize>() * 2 {
+                            return Err(ParseError::ChunkedEncoding);
+                        }
+                        raw.bytes_left_in_chunk = raw.bytes_left_in_chunk * 16 + v;
+                        raw._hex_count += 1;
+                        self.offset += 1;
+                    }
+                    raw._hex_count = 0;
+                    raw._state = IN_CHUNK_EXT;
+                }
+                IN_CHUNK_EXT => {
+                    if !skip_line(buf, &mut self.offset) {
+                        return Ok(None);
+                    }
+                    if raw.bytes_left_in_chunk != 0 {
+                        raw._state = IN_CHUNK_DATA;
+                    } else if raw.consume_trailer != 0 {
+                        raw._state = IN_TRAILERS_LINE_HEAD;
+                    } else {
+                        self.complete = true;
+                        return Ok(None);
+                    }
+                }
+                IN_CHUNK_DATA => {
+                    let available = buf.len() - self.offset;
+                    if available == 0 {
+                        return Ok(None);
+                    }
+                    let len = cmp::min(available, raw.bytes_left_in_chunk);
+                    let data = &buf[self.offset..self.offset + len];
+                    self.offset += len;
+                    raw.bytes_left_in_chunk -= len;
+                    if raw.bytes_left_in_chunk == 0 {
+                        raw._state = IN_CHUNK_CRLF;
+                    }
+                    return Ok(Some(data));
+                }
+                IN_CHUNK_CRLF => {
+                    match skip_cr(buf, &mut self.offset) {
+                        Some(b'\n') => self.offset += 1,
+                        Some(_) => return Err(ParseError::ChunkedEncoding),
+                        None => return Ok(None),
+                    }
+                    raw._state = IN_CHUNK_SIZE;
+                }
+                IN_TRAILERS_LINE_HEAD => {
+                    let b = match skip_cr(buf, &mut self.offset) {
+                        Some(b) => b,
+                        None => return Ok(None),
+                    };
+                    self.offset += 1;
+                    if b == b'\n' {
+                        self.complete = true;
+                        return Ok(None);
+                    }
+                    raw._state = IN_TRAILERS_LINE_MIDDLE;
+                }
+                IN_TRAILERS_LINE_MIDDLE => {
+                    if !skip_line(buf, &mut self.offset) {
+                        return Ok(None);
+                    }
+                    raw._state = IN_TRAILERS_LINE_HEAD;
+                }
+                _ => unreachable!("chunked decoder is corrupt"),
+            }
+        }
+    }
+}
+
+impl<'d, 'buf> Iterator for ChunkSlices<'d, 'buf> {
+    type Item = Result<&'buf [u8], ParseError>;
+
+    fn next(&mut self) -> Option<Result<&'buf [u8], ParseError>> {
+        if self.complete || self.failed {
+            return None;
+        }
+        match self.next_slice() {
+            Ok(data) => data.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// Moves `offset` past the next LF, or to the end of `buf` if there is none.
+fn skip_line(buf: &[u8], offset: &mut usize) -> bool {
+    match buf[*offset..].iter().position(|&b| b == b'\n') {
+        Some(i) => {
+            *offset += i + 1;
+            true
+        }
+        None => {
+            *offset = buf.len();
+            false
+        }
+    }
+}
+
+// Moves `offset` past any CRs and returns the byte after them.
+fn skip_cr(buf: &[u8], offset: &mut usize) -> Option<u8> {
+    while let Some(&b) = buf.get(*offset) {
+        if b != b'\r' {
+            return Some(b);
+        }
+        *offset += 1;
+    }
+    None
+}
+
+/// Iterator over the complete chunks at the start of a chunked body,
+/// including their extensions.
+///
+/// Unlike `ChunkSlices`, a chunk is only yielded once all of it is in the
+/// buffer. Iteration stops before the first incomplete chunk, after the last
+/// chunk, or after the first error. In the first case, `offset` is where the
+/// incomplete chunk starts; in the second, `remainder` starts with the
+/// trailer section.
+#[derive(Clone, Debug)]
+pub struct Chunks<'buf> {
+    buf: &'buf [u8],
+    offset: usize,
+    max_extension_len: usize,
+    complete: bool,
+    failed: bool,
+}
+
+impl<'buf> Chunks<'buf> {
+    /// Creates an iterator that allows up to 1024 bytes of extensions per
+    /// chunk.
+    pub fn new(buf: &'buf [u8]) -> Chunks<'buf> {
+        Chunks::with_max_extension_len(buf, 1024)
+    }
+
+    /// Creates an iterator that fails with `ChunkExtensionTooLong` when the
+    /// extensions of a chunk exceed `max_extension_len` bytes.
+    pub fn with_max_extension_len(buf: &'buf [u8], max_extension_len: usize) -> Chunks<'buf> {
+        Chunks {
+            buf,
+            offset: 0,
+            max_extension_len,
+            complete: false,
+            failed: false,
+        }
+    }
+
+    /// The number of bytes of the buffer taken up by the chunks yielded so
+    /// far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the last chunk was reached.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The part of the buffer after the chunks yielded so far.
+    pub fn remainder(&self) -> &'buf [u8] {
+        &self.buf[self.offset..]
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Chunk<'buf>>, ParseError> {
+        let buf = &self.buf[self.offset..];
+
+        let digits = buf.iter().take_while(|b| b.is_ascii_hexdigit()).count();
+        if digits > mem::size_of::<usize>() * 2 {
+            return Err(ParseError::ChunkedEncoding);
+        }
+        if digits == buf.len() {
+            return Ok(None);
+        }
+        if digits == 0 {
+            return Err(ParseError::ChunkedEncoding);
+        }
+        let size = buf[..digits]
+            .iter()
+            .fold(0, |size, &b| size * 16 + (b as char).to_digit(16).unwrap() as usize);
+
+        let rest = &buf[digits..];
+        let line_len = match rest.iter().position(|&b| b == b'\n') {
+            Some(line_len) => line_len,
+            None if rest.len() > self.max_extension_len + 1 => {
+                return Err(ParseError::ChunkExtensionTooLong)
+            }
+            None => return Ok(None),
+        };
+        let extensions = match rest[..line_len].split_last() {
+            Some((&b'\r', extensions)) => extensions,
+            _ => &rest[..line_len],
+        };
+        if extensions.len() > self.max_extension_len {
+            return Err(ParseError::ChunkExtensionTooLong);
+        }
+        let mut unchecked = extensions;
+        while next_extension(&mut unchecked)?.is_some() {}
+
+        let data_start = digits + line_len + 1;
+        if size == 0 {
+            self.offset += data_start;
+            self.complete = true;
+            return Ok(Some(Chunk {
+                extensions: ChunkExtensions { rest: extensions },
+                data: &[],
+            }));
+        }
+        if buf.len() - data_start < size {
+            return Ok(None);
+        }
+        let mut end = data_start + size;
+        match skip_cr(buf, &mut end) {
+            Some(b'\n') => end += 1,
+            Some(_) => return Err(ParseError::ChunkedEncoding),
+            None => return Ok(None),
+        }
+
+        self.offset += end;
+        Ok(Some(Chunk {
+            extensions: ChunkExtensions { rest: extensions },
+            data: &buf[data_start..data_start + size],
+        }))
+    }
+}
+
+impl<'buf> Iterator for Chunks<'buf> {
+    type Item = Result<Chunk<'buf>, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Chunk<'buf>, ParseError>> {
+        if self.complete || self.failed {
+            return None;
+        }
+        match self.next_chunk() {
+            Ok(chunk) => chunk.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A chunk yielded by `Chunks`.
+#[derive(Clone, Copy, Debug)]
+pub struct Chunk<'buf> {
+    extensions: ChunkExtensions<'buf>,
+    data: &'buf [u8],
+}
+
+impl<'buf> Chunk<'buf> {
+    /// The chunk size, which is zero for the last chunk.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn extensions(&self) -> ChunkExtensions<'buf> {
+        self.extensions
+    }
+
+    pub fn data(&self) -> &'buf [u8] {
+        self.data
+    }
+}
+
+/// Iterator over the extensions of a chunk.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkExtensions<'buf> {
+    rest: &'buf [u8],
+}
+
+impl<'buf> Iterator for ChunkExtensions<'buf> {
+    type Item = ChunkExtension<'buf>;
+
+    fn next(&mut self) -> Option<ChunkExtension<'buf>> {
+        // The whole list was validated before the chunk was yielded.
+        next_extension(&mut self.rest).ok().and_then(|extension| extension)
+    }
+}
+
+/// A chunk extension: `;name` or `;name=value`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkExtension<'buf> {
+    name: &'buf [u8],
+    value: Option<&'buf [u8]>,
+}
+
+impl<'buf> ChunkExtension<'buf> {
+    pub fn name(&self) -> &'buf [u8] {
+        self.name
+    }
+
+    /// The value, with the quotes and escapes of a quoted string removed.
+    /// Only allocates if there were escapes.
+    pub fn value(&self) -> Option<Cow<'buf, [u8]>> {
+        let value = self.value?;
+        if value.first() != Some(&b'"') {
+            return Some(Cow::Borrowed(value));
+        }
+        let inner = &value[1..value.len() - 1];
+        if !inner.contains(&b'\\') {
+            return Some(Cow::Borrowed(inner));
+        }
+        let mut unescaped = Vec::with_capacity(inner.len());
+        let mut bytes = inner.iter();
+        while let Some(&b) = bytes.next() {
+            unescaped.push(if b == b'\\' { *bytes.next().unwrap() } else { b });
+        }
+        Some(Cow::Owned(unescaped))
+    }
+
+    /// The value as sent, including any quotes.
+    pub fn raw_value(&self) -> Option<&'buf [u8]> {
+        self.value
+    }
+}
+
+// Takes the next extension off the front of `rest`, following
+// `*( BWS ";" BWS name [ BWS "=" BWS ( token / quoted-string ) ] ) BWS`.
+fn next_extension<'buf>(rest: &mut &'buf [u8]) -> Result<Option<ChunkExtension<'buf>>, ParseError> {
+    let bytes = trim_start(rest);
+    let bytes = match bytes.split_first() {
+        None => return Ok(None),
+        Some((&b';', bytes)) => trim_start(bytes),
+        Some(_) => return Err(ParseError::ChunkedEncoding),
+    };
+
+    let name_len = token_len(bytes);
+    if name_len == 0 {
+        return Err(ParseError::ChunkedEncoding);
+    }
+    let (name, bytes) = bytes.split_at(name_len);
+
+    let after_name = trim_start(bytes);
+    let (value, bytes) = match after_name.split_first() {
+        Some((&b'=', value)) => {
+            let value = trim_start(value);
+            let value_len = match value.first() {
+                Some(&b'"') => quoted_string_len(value)?,
+                _ => token_len(value),
+            };
+            if value_len == 0 {
+                return Err(ParseError::ChunkedEncoding);
+            }
+            let (value, bytes) = value.split_at(value_len);
+            (Some(value), bytes)
+        }
+        _ => (None, bytes),
+    };
+
+    *rest = bytes;
+    Ok(Some(ChunkExtension { name, value }))
+}
+
+fn token_len(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|&&b| is_token_char(b)).count()
+}
+
+// Returns the length of the quoted string at the start of `bytes`,
+// including both quotes.
+fn quoted_string_len(bytes: &[u8]) -> Result<usize, ParseError> {
+    let mut i = 1;
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'"' => return Ok(i + 1),
+            b'\\' => i += 1,
+            _ => {}
+        }
+        match bytes.get(i) {
+            Some(&b) if b == b'\t' || (b >= 0x20 && b != 0x7f) => i += 1,
+            _ => return Err(ParseError::ChunkedEncoding),
+        }
+    }
+    Err(ParseError::ChunkedEncoding)
+}
@@ -0,0 +1,274 @@
+use std::cmp;
+
+use super::{phr_header, HeaderBuffer, Headers, ParseError, Request, Response, Status};
+use strict::{self, StartLine};
+
+/// Limits enforced on top of the C parser, which accepts request lines and
+/// header fields of any length.
+///
+/// Every limit is off until set. Each one fails with its own `ParseError`:
+/// `HeadTooLarge`, `TargetTooLong`, `HeaderNameTooLong`,
+/// `HeaderValueTooLong` and `TooManyHeaders`.
+///
+/// Field limits are checked once the head is complete, while the head and
+/// request-target limits also apply to a partial head so that an oversized
+/// one is rejected without waiting for the rest of it.
+///
+/// The config can also turn on strict RFC 9112 conformance, see `strict`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserConfig {
+    max_head_len: usize,
+    max_target_len: usize,
+    max_header_name_len: usize,
+    max_header_value_len: usize,
+    max_headers: usize,
+    strict: bool,
+}
+
+impl ParserConfig {
+    pub fn new() -> ParserConfig {
+        ParserConfig {
+            max_head_len: usize::MAX,
+            max_target_len: usize::MAX,
+            max_header_name_len: usize::MAX,
+            max_header_value_len: usize::MAX,
+            max_headers: usize::MAX,
+            strict: false,
+        }
+    }
+
+    /// Limits the head, from the start of the buffer to the end of the empty
+    /// line after the headers, to `len` bytes.
+    pub fn max_head_len(&mut self, len: usize) -> &mut ParserConfig {
+        self.max_head_len = len;
+        self
+    }
+
+    /// Limits the request-target to `len` bytes.
+    pub fn max_target_len(&mut self, len: usize) -> &mut ParserConfig {
+        self.max_target_len = len;
+        self
+    }
+
+    /// Limits each header name to `len` bytes.
+    pub fn max_header_name_len(&mut self, len: usize) -> &mut ParserConfig {
+        self.max_header_name_len = len;
+        self
+    }
+
+    /// Limits each header value to `len` bytes. Folded values are limited
+    /// line by line.
+    pub fn max_header_value_len(&mut self, len: usize) -> &mut ParserConfig {
+        self.max_header_value_len = len;
+        self
+    }
+
+    /// Limits the number of header lines, regardless of how much storage is
+    /// passed to the parser.
+    pub fn max_headers(&mut self, count: usize) -> &mut ParserConfig {
+        self.max_headers = count;
+        self
+    }
+
+    /// Rejects heads that the C parser leniently accepts or only reports as
+    /// `Malformed`, with a specific error for each rule:
+    ///
+    /// * `BareLf` for a line ending in LF without CR,
+    /// * `ObsFold` for a header value folded across lines,
+    /// * `WhitespaceBeforeColon` for whitespace between a header name and
+    ///   the colon,
+    /// * `InvalidMethod` and `InvalidHeaderName` for names that are not
+    ///   tokens, and
+    /// * `InvalidTarget` for a request-target with anything but visible
+    ///   ASCII characters.
+    ///
+    /// The rules are checked line by line, so a partial head fails as soon
+    /// as an offending line is complete.
+    pub fn strict(&mut self, strict: bool) -> &mut ParserConfig {
+        self.strict = strict;
+        self
+    }
+
+    /// Like `Request::parse`, with the limits applied.
+    pub fn parse_request<'buf, 'h>(&self,
+                                   buf: &'buf [u8],
+                                   headers: &'h mut [phr_header])
+                                   -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        self.parse_request_with_last_len(buf, 0, headers)
+    }
+
+    /// Like `Request::parse_with_last_len`, with the limits applied. The
+    /// head and request-target limits are checked on every partial attempt.
+    ///
+    /// Strict checks start over from the beginning of `buf` on each call;
+    /// `RequestHeadParser::with_config` keeps track of what it has checked.
+    pub fn parse_request_with_last_len<'buf, 'h>(&self,
+                                                 buf: &'buf [u8],
+                                                 last_len: usize,
+                                                 headers: &'h mut [phr_header])
+                                                 -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        self.parse_request_resuming(buf, last_len, &mut 0, headers)
+    }
+
+    // Like `parse_request_with_last_len`, but resumes the strict checks from
+    // `strict_checked` and advances it past the lines that passed.
+    pub(crate) fn parse_request_resuming<'buf, 'h>
+        (&self,
+         buf: &'buf [u8],
+         last_len: usize,
+         strict_checked: &mut usize,
+         headers: &'h mut [phr_header])
+         -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        if self.strict {
+            *strict_checked = strict::check(buf, StartLine::Request, *strict_checked)?;
+        }
+        let headers = self.limit_headers(headers);
+        match Request::parse_with_last_len(buf, last_len, headers)? {
+            Status::Complete(consumed, req) => {
+                self.check_head_len(consumed)?;
+                self.check_target_len(req.path().len())?;
+                self.check_headers(&req.headers())?;
+                Ok(Status::Complete(consumed, req))
+            }
+            Status::Partial => {
+                self.check_head_len(buf.len())?;
+                if self.max_target_len != usize::MAX {
+                    self.check_target_len(partial_target_len(buf))?;
+                }
+                Ok(Status::Partial)
+            }
+        }
+    }
+
+    /// Like `Request::parse_growing`, with the limits applied.
+    pub fn parse_request_growing<'buf, 'h>(&self,
+                                           buf: &'buf [u8],
+                                           headers: &'h mut HeaderBuffer)
+                                           -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| self.parse_request(buf, headers))
+    }
+
+    /// Like `Response::parse`, with the limits applied.
+    pub fn parse_response<'buf, 'h>(&self,
+                                    buf: &'buf [u8],
+                                    headers: &'h mut [phr_header])
+                                    -> Result<Status<Response<'buf, 'h>>, ParseError> {
+        self.check_strict(buf, StartLine::Status)?;
+        let headers = self.limit_headers(headers);
+        match Response::parse(buf, headers)? {
+            Status::Complete(consumed, res) => {
+                self.check_head_len(consumed)?;
+                self.check_headers(&res.headers())?;
+                Ok(Status::Complete(consumed, res))
+            }
+            Status::Partial => {
+                self.check_head_len(buf.len())?;
+                Ok(Status::Partial)
+            }
+        }
+    }
+
+    /// Like `Response::parse_growing`, with the limits applied.
+    pub fn parse_response_growing<'buf, 'h>(&self,
+                                            buf: &'buf [u8],
+                                            headers: &'h mut HeaderBuffer)
+                                            -> Result<Status<Response<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| self.parse_response(buf, headers))
+    }
+
+    /// Like `Headers::parse`, with the limits applied.
+    pub fn parse_headers<'buf, 'h>(&self,
+                                   buf: &'buf [u8],
+                                   headers: &'h mut [phr_header])
+                                   -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        self.check_strict(buf, StartLine::None)?;
+        let headers = self.limit_headers(headers);
+        match Headers::parse(buf, headers)? {
+            Status::Complete(consumed, headers) => {
+                self.check_head_len(consumed)?;
+                self.check_headers(&headers)?;
+                Ok(Status::Complete(consumed, headers))
+            }
+            Status::Partial => {
+                self.check_head_len(buf.len())?;
+                Ok(Status::Partial)
+            }
+        }
+    }
+
+    /// Like `Headers::parse_growing`, with the limits applied.
+    pub fn parse_headers_growing<'buf, 'h>(&self,
+                                           buf: &'buf [u8],
+                                           headers: &'h mut HeaderBuffer)
+                                           -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| self.parse_headers(buf, headers))
+    }
+
+    // Cuts the storage down so that the C parser stops at `max_headers`,
+    // which then surfaces as `TooManyHeaders`.
+    fn limit_headers<'h>(&self, headers: &'h mut [phr_header]) -> &'h mut [phr_header] {
+        let len = cmp::min(headers.len(), self.max_headers);
+        &mut headers[..len]
+    }
+
+    fn check_strict(&self, buf: &[u8], start_line: StartLine) -> Result<(), ParseError> {
+        if self.strict {
+            strict::check(buf, start_line, 0).map(|_| ())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_head_len(&self, len: usize) -> Result<(), ParseError> {
+        if len > self.max_head_len {
+            Err(ParseError::HeadTooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_target_len(&self, len: usize) -> Result<(), ParseError> {
+        if len > self.max_target_len {
+            Err(ParseError::TargetTooLong)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_headers(&self, headers: &Headers) -> Result<(), ParseError> {
+        for header in headers.iter() {
+            if header.name().len() > self.max_header_name_len {
+                return Err(ParseError::HeaderNameTooLong);
+            }
+            if header.value().len() > self.max_header_value_len {
+                return Err(ParseError::HeaderValueTooLong);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> ParserConfig {
+        ParserConfig::new()
+    }
+}
+
+// Returns how much of the request-target has arrived in a partial request
+// line, or 0 if it has not started yet.
+fn partial_target_len(buf: &[u8]) -> usize {
+    let line = match buf.iter().position(|&b| b != b'\r' && b != b'\n') {
+        Some(start) => &buf[start..],
+        None => return 0,
+    };
+    let line = match line.iter().position(|&b| b == b'\n') {
+        Some(end) => &line[..end],
+        None => line,
+    };
+    match line.iter().position(|&b| b == b' ') {
+        Some(method_len) => {
+            line[method_len + 1..].iter().take_while(|&&b| b != b' ' && b != b'\r').count()
+        }
+        None => 0,
+    }
+}
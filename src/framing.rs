@@ -0,0 +1,139 @@
+use super::{trim_end, trim_start, Headers, Method, ParseError, Request, Response};
+
+/// How the body following a message head is delimited, as determined by
+/// RFC 9112 section 6.3.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyFraming {
+    /// There is no body. This includes responses to HEAD, 1xx, 204 and 304
+    /// responses, 2xx responses to CONNECT (after which the connection
+    /// becomes a tunnel) and requests without Content-Length or
+    /// Transfer-Encoding.
+    Empty,
+    /// The body is exactly this many bytes long.
+    ContentLength(u64),
+    /// The body uses the chunked transfer coding.
+    Chunked,
+    /// The body runs until the connection is closed. Only for responses.
+    CloseDelimited,
+}
+
+impl BodyFraming {
+    /// Determines the framing of the body following `req`.
+    ///
+    /// A Transfer-Encoding takes precedence over Content-Length. Requests
+    /// whose final transfer coding is not chunked cannot be delimited and are
+    /// rejected, as are HTTP/1.0 requests with a Transfer-Encoding (RFC 9112
+    /// section 6.1).
+    pub fn for_request(req: &Request) -> Result<BodyFraming, ParseError> {
+        let headers = req.headers();
+        match transfer_encoding_is_chunked(&headers) {
+            Some(_) if req.minor_version() == 0 => Err(ParseError::TransferEncoding),
+            Some(true) => Ok(BodyFraming::Chunked),
+            Some(false) => Err(ParseError::TransferEncoding),
+            None => {
+                Ok(match content_length(&headers)? {
+                    Some(len) => BodyFraming::ContentLength(len),
+                    None => BodyFraming::Empty,
+                })
+            }
+        }
+    }
+
+    /// Determines the framing of the body following `res`, sent in reply to
+    /// a request with the method `request_method`.
+    ///
+    /// An HTTP/1.0 response with a Transfer-Encoding is rejected, since its
+    /// framing cannot be trusted (RFC 9112 section 6.1).
+    pub fn for_response(res: &Response,
+                        request_method: Method)
+                        -> Result<BodyFraming, ParseError> {
+        let status = res.status();
+        if request_method == Method::Head || (100..200).contains(&status) || status == 204 ||
+           status == 304 {
+            return Ok(BodyFraming::Empty);
+        }
+        if request_method == Method::Connect && (200..300).contains(&status) {
+            return Ok(BodyFraming::Empty);
+        }
+
+        let headers = res.headers();
+        match transfer_encoding_is_chunked(&headers) {
+            Some(_) if res.minor_version() == 0 => Err(ParseError::TransferEncoding),
+            Some(true) => Ok(BodyFraming::Chunked),
+            Some(false) => Ok(BodyFraming::CloseDelimited),
+            None => {
+                Ok(match content_length(&headers)? {
+                    Some(len) => BodyFraming::ContentLength(len),
+                    None => BodyFraming::CloseDelimited,
+                })
+            }
+        }
+    }
+}
+
+// Splits a list-valued header into its elements, dropping empty ones.
+pub(crate) fn list_elements(value: &[u8]) -> impl Iterator<Item = &[u8]> {
+    value.split(|&b| b == b',').map(|element| trim_end(trim_start(element))).filter(|element| {
+        !element.is_empty()
+    })
+}
+
+// Returns whether the final transfer coding is chunked, or `None` if there is
+// no Transfer-Encoding header.
+pub(crate) fn transfer_encoding_is_chunked(headers: &Headers) -> Option<bool> {
+    let mut present = false;
+    let mut chunked = false;
+    for field in headers.fields() {
+        if !field.name().eq_ignore_ascii_case(b"transfer-encoding") {
+            continue;
+        }
+        present = true;
+        let value = field.value();
+        if let Some(coding) = list_elements(&value).last() {
+            chunked = is_chunked(coding);
+        }
+    }
+    if present { Some(chunked) } else { None }
+}
+
+pub(crate) fn is_chunked(coding: &[u8]) -> bool {
+    let name = coding.split(|&b| b == b';').next().unwrap_or(coding);
+    trim_end(name).eq_ignore_ascii_case(b"chunked")
+}
+
+// Returns the Content-Length, accepting repeated identical values.
+pub(crate) fn content_length(headers: &Headers) -> Result<Option<u64>, ParseError> {
+    let mut len = None;
+    for field in headers.fields() {
+        if !field.name().eq_ignore_ascii_case(b"content-length") {
+            continue;
+        }
+        let value = field.value();
+        let mut elements = 0;
+        for element in list_elements(&value) {
+            elements += 1;
+            let n = parse_decimal(element).ok_or(ParseError::ContentLength)?;
+            match len {
+                Some(prev) if prev != n => return Err(ParseError::ConflictingContentLength),
+                _ => len = Some(n),
+            }
+        }
+        if elements == 0 {
+            return Err(ParseError::ContentLength);
+        }
+    }
+    Ok(len)
+}
+
+pub(crate) fn parse_decimal(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits.iter().try_fold(0u64, |n, &b| {
+        if b.is_ascii_digit() {
+            n.checked_mul(10).and_then(|n| n.checked_add(u64::from(b - b'0')))
+        } else {
+            None
+        }
+    })
+}
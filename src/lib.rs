@@ -1,7 +1,36 @@
 extern crate libc;
 
 use libc::{c_char, c_int, size_t, ssize_t};
+use std::borrow::Cow;
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
 use std::ptr;
+use std::slice;
+
+pub use chunks::{Chunk, ChunkExtension, ChunkExtensions, ChunkSlices, Chunks};
+pub use config::ParserConfig;
+pub use framing::BodyFraming;
+pub use method::Method;
+pub use path::{normalize_path, NormalizedPath, PathAnomaly};
+pub use pipeline::{Pipeline, Pipelined};
+pub use query::{Pairs, Query, QueryPart, Values};
+pub use serialize::{encode_request, encode_response, reason_phrase, write_request, write_response,
+                    ChunkedEncoder, SerializeError};
+pub use target::{Authority, RequestTarget};
+
+mod chunks;
+mod config;
+mod framing;
+mod method;
+mod path;
+mod pipeline;
+mod query;
+mod serialize;
+mod strict;
+mod target;
+pub mod smuggling;
 
 // /* contains name and value of a header (name == NULL if is a continuing line
 //  * of a multiline header */
@@ -39,7 +68,7 @@ impl Default for phr_header {
 //     char _state;
 // };
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct phr_chunked_decoder {
     pub bytes_left_in_chunk: size_t, // number of bytes left in current chunk
     pub consume_trailer: c_char, // if trailing headers should be consumed
@@ -107,3 +136,1002 @@ extern "C" {
     // int phr_decode_chunked_is_in_data(struct phr_chunked_decoder *decoder);
     pub fn phr_decode_chunked_is_in_data(decoder: *mut phr_chunked_decoder) -> c_int;
 }
+
+unsafe fn slice_from_raw<'a>(pointer: *const c_char, len: size_t) -> &'a [u8] {
+    if pointer.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(pointer as *const u8, len)
+    }
+}
+
+/// The outcome of parsing a possibly incomplete message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status<T> {
+    /// The message is complete: the number of bytes consumed and the parsed
+    /// value.
+    Complete(usize, T),
+    /// More data is needed.
+    Partial,
+}
+
+impl<T> Status<T> {
+    pub fn is_complete(&self) -> bool {
+        match *self {
+            Status::Complete(..) => true,
+            Status::Partial => false,
+        }
+    }
+
+    pub fn is_partial(&self) -> bool {
+        !self.is_complete()
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Status<U> {
+        match self {
+            Status::Complete(consumed, value) => Status::Complete(consumed, f(value)),
+            Status::Partial => Status::Partial,
+        }
+    }
+}
+
+/// Why a message was rejected by one of the safe parsers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The message is not well-formed.
+    Malformed,
+    /// The minor version does not fit in a `u8`.
+    Version,
+    /// The status code does not fit in a `u16`.
+    StatusCode,
+    /// The chunked encoding is invalid.
+    ChunkedEncoding,
+    /// The extensions of a chunk are longer than allowed.
+    ChunkExtensionTooLong,
+    /// The head has more headers than the storage passed to the parser.
+    /// `needed` is the number of header lines found, which is only a lower
+    /// bound if the head is not complete yet.
+    TooManyHeaders { needed: usize },
+    /// A header value is folded across lines (obs-fold).
+    ObsFold,
+    /// A Content-Length value is not a valid length.
+    ContentLength,
+    /// Content-Length values disagree.
+    ConflictingContentLength,
+    /// A request's final transfer coding is not chunked, so its body cannot
+    /// be delimited.
+    TransferEncoding,
+    /// The head is longer than `ParserConfig::max_head_len`.
+    HeadTooLarge,
+    /// The request-target is longer than `ParserConfig::max_target_len`.
+    TargetTooLong,
+    /// A header name is longer than `ParserConfig::max_header_name_len`.
+    HeaderNameTooLong,
+    /// A header value is longer than `ParserConfig::max_header_value_len`.
+    HeaderValueTooLong,
+    /// A line ends in LF without CR. Only reported in strict mode.
+    BareLf,
+    /// There is whitespace between a header name and the colon. Only
+    /// reported in strict mode.
+    WhitespaceBeforeColon,
+    /// The method is not a token. Only reported in strict mode.
+    InvalidMethod,
+    /// The request-target has characters other than visible ASCII, which is
+    /// only reported in strict mode, or does not have the form required by
+    /// `RequestTarget::parse`.
+    InvalidTarget,
+    /// A header name is not a token. Only reported in strict mode.
+    InvalidHeaderName,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ParseError::Malformed => "malformed HTTP message",
+            ParseError::Version => "unsupported HTTP version",
+            ParseError::StatusCode => "invalid status code",
+            ParseError::ChunkedEncoding => "invalid chunked encoding",
+            ParseError::ChunkExtensionTooLong => "chunk extension too long",
+            ParseError::TooManyHeaders { .. } => "too many headers",
+            ParseError::ObsFold => "folded header value",
+            ParseError::ContentLength => "invalid Content-Length",
+            ParseError::ConflictingContentLength => "conflicting Content-Length values",
+            ParseError::TransferEncoding => "unsupported Transfer-Encoding",
+            ParseError::HeadTooLarge => "message head too large",
+            ParseError::TargetTooLong => "request-target too long",
+            ParseError::HeaderNameTooLong => "header name too long",
+            ParseError::HeaderValueTooLong => "header value too long",
+            ParseError::BareLf => "line ending without CR",
+            ParseError::WhitespaceBeforeColon => "whitespace before colon in header",
+            ParseError::InvalidMethod => "invalid method",
+            ParseError::InvalidTarget => "invalid request-target",
+            ParseError::InvalidHeaderName => "invalid header name",
+        })
+    }
+}
+
+impl error::Error for ParseError {}
+
+// Runs one of the phr_parse_* functions through `parse` with the caller's
+// header storage, returning the number of bytes consumed and headers filled.
+//
+// The C parser rejects a head that has more headers than slots exactly like
+// malformed input, so a failure with every slot in use is looked at again to
+// tell the two apart.
+fn parse_with<F>(buf: &[u8],
+                 headers: &mut [phr_header],
+                 mut parse: F)
+                 -> Result<Option<(usize, usize)>, ParseError>
+    where F: FnMut(&mut [phr_header], &mut size_t) -> c_int
+{
+    let mut num_headers = headers.len();
+    let ret = parse(headers, &mut num_headers);
+
+    match ret {
+        -2 => Ok(None),
+        -1 if num_headers == headers.len() => {
+            match headers_needed(buf, headers, &mut parse) {
+                Some(needed) => Err(ParseError::TooManyHeaders { needed }),
+                None => Err(ParseError::Malformed),
+            }
+        }
+        ret if ret < 0 => Err(ParseError::Malformed),
+        ret => Ok(Some((ret as usize, num_headers))),
+    }
+}
+
+// Given header storage that was filled completely before the parser gave up,
+// returns how many header lines `buf` holds if the parser stopped because it
+// ran out of slots, or `None` if the input itself is malformed. The count is
+// confirmed by parsing again with that many slots, since a malformed line
+// after the last slot looks like any other header line.
+fn headers_needed<F>(buf: &[u8], headers: &[phr_header], parse: &mut F) -> Option<usize>
+    where F: FnMut(&mut [phr_header], &mut size_t) -> c_int
+{
+    let last = match headers.last() {
+        Some(last) => last,
+        None => {
+            // Without a single slot there is no parsed header to resume
+            // from, so find out whether the first one parses.
+            let mut one = [phr_header::default()];
+            let mut num_headers = one.len();
+            return match parse(&mut one, &mut num_headers) {
+                -1 if num_headers == one.len() => headers_needed(buf, &one, parse),
+                -1 => None,
+                _ => Some(one.len()),
+            };
+        }
+    };
+
+    let mut pos = last.value as usize - buf.as_ptr() as usize + last.value_len;
+    pos += if buf[pos] == b'\r' { 2 } else { 1 };
+    let needed = match buf.get(pos) {
+        None | Some(&b'\r') | Some(&b'\n') => return None,
+        Some(_) => headers.len() + count_header_lines(&buf[pos..]),
+    };
+
+    let mut more = vec![phr_header::default(); needed];
+    let mut num_headers = more.len();
+    match parse(&mut more, &mut num_headers) {
+        -1 if num_headers == more.len() => headers_needed(buf, &more, parse),
+        -1 => None,
+        _ => Some(needed),
+    }
+}
+
+// Counts the header lines at the start of `buf` up to the empty line ending
+// the head, or to the end of `buf` if the head is incomplete.
+fn count_header_lines(buf: &[u8]) -> usize {
+    let mut count = 0;
+    let mut rest = buf;
+    loop {
+        match rest.first() {
+            None | Some(&b'\r') | Some(&b'\n') => return count,
+            Some(_) => count += 1,
+        }
+        match rest.iter().position(|&b| b == b'\n') {
+            Some(end) => rest = &rest[end + 1..],
+            None => return count,
+        }
+    }
+}
+
+fn minor_version_from_raw(minor_version: c_int) -> Result<u8, ParseError> {
+    if minor_version < 0 || minor_version > u8::MAX as c_int {
+        Err(ParseError::Version)
+    } else {
+        Ok(minor_version as u8)
+    }
+}
+
+/// A single header line borrowed from the parsed buffer.
+///
+/// Continuation lines of a multiline header have an empty name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header<'buf> {
+    name: &'buf [u8],
+    value: &'buf [u8],
+}
+
+impl<'buf> Header<'buf> {
+    unsafe fn from_raw(raw: &phr_header) -> Header<'buf> {
+        Header {
+            name: slice_from_raw(raw.name, raw.name_len),
+            value: slice_from_raw(raw.value, raw.value_len),
+        }
+    }
+
+    pub fn name(&self) -> &'buf [u8] {
+        self.name
+    }
+
+    pub fn value(&self) -> &'buf [u8] {
+        self.value
+    }
+
+    /// Returns `true` if this line continues the value of the previous header.
+    pub fn is_continuation(&self) -> bool {
+        self.name.is_empty()
+    }
+}
+
+/// The headers filled in by a parser, borrowing both the parsed buffer and
+/// the caller's `phr_header` storage.
+#[derive(Clone, Copy)]
+pub struct Headers<'buf, 'h> {
+    raw: &'h [phr_header],
+    _buf: PhantomData<&'buf [u8]>,
+}
+
+impl<'buf, 'h> Headers<'buf, 'h> {
+    // The caller guarantees that every entry of `raw` points into a buffer
+    // that lives for `'buf`.
+    unsafe fn from_raw(raw: &'h [phr_header]) -> Headers<'buf, 'h> {
+        Headers {
+            raw,
+            _buf: PhantomData,
+        }
+    }
+
+    /// Parses a header section from `buf`, storing up to `headers.len()`
+    /// headers.
+    pub fn parse(buf: &'buf [u8],
+                 headers: &'h mut [phr_header])
+                 -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        Headers::parse_with_last_len(buf, 0, headers)
+    }
+
+    /// Like `parse`, but retries with more header storage from `headers`
+    /// when needed.
+    pub fn parse_growing(buf: &'buf [u8],
+                         headers: &'h mut HeaderBuffer)
+                         -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| Headers::parse(buf, headers))
+    }
+
+    /// Like `parse`, but only rescans from `last_len`, the length of `buf`
+    /// at the previous partial attempt.
+    pub fn parse_with_last_len(buf: &'buf [u8],
+                               last_len: usize,
+                               headers: &'h mut [phr_header])
+                               -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        let parsed = parse_with(buf, headers, |headers, num_headers| unsafe {
+            phr_parse_headers(buf.as_ptr() as *const c_char,
+                              buf.len(),
+                              headers.as_mut_ptr(),
+                              num_headers,
+                              last_len)
+        })?;
+        let (consumed, num_headers) = match parsed {
+            Some(parsed) => parsed,
+            None => return Ok(Status::Partial),
+        };
+
+        let headers: &'h [phr_header] = headers;
+        unsafe { Ok(Status::Complete(consumed, Headers::from_raw(&headers[..num_headers]))) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'buf, 'h> {
+        Iter {
+            inner: self.raw.iter(),
+            _buf: PhantomData,
+        }
+    }
+
+    /// Returns the value of the first header called `name`, compared ASCII
+    /// case-insensitively.
+    ///
+    /// Only the first line of a folded value is returned. Use `fields` to
+    /// get the whole value of a header that may be folded.
+    pub fn get(&self, name: &[u8]) -> Option<&'buf [u8]> {
+        self.get_all(name).next()
+    }
+
+    /// Returns the values of every header called `name`, compared ASCII
+    /// case-insensitively, in the order they appear.
+    ///
+    /// As with `get`, folded values are cut off after their first line.
+    pub fn get_all<'n>(&self, name: &'n [u8]) -> GetAll<'buf, 'h, 'n> {
+        GetAll {
+            inner: self.iter(),
+            name,
+        }
+    }
+
+    /// Returns `true` if there is a header called `name`, compared ASCII
+    /// case-insensitively.
+    pub fn contains(&self, name: &[u8]) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Returns an iterator over the header fields, with continuation lines
+    /// grouped under the header they continue.
+    pub fn fields(&self) -> Fields<'buf, 'h> {
+        Fields {
+            raw: self.raw,
+            _buf: PhantomData,
+        }
+    }
+
+    /// Returns `ParseError::ObsFold` if any header value is folded across
+    /// lines, for callers that reject obs-fold outright.
+    pub fn reject_obs_fold(&self) -> Result<(), ParseError> {
+        if self.iter().any(|header| header.is_continuation()) {
+            Err(ParseError::ObsFold)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'buf, 'h> fmt::Debug for Headers<'buf, 'h> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'buf, 'h> IntoIterator for Headers<'buf, 'h> {
+    type Item = Header<'buf>;
+    type IntoIter = Iter<'buf, 'h>;
+
+    fn into_iter(self) -> Iter<'buf, 'h> {
+        self.iter()
+    }
+}
+
+/// Iterator over the lines of a `Headers`.
+#[derive(Clone)]
+pub struct Iter<'buf, 'h> {
+    inner: slice::Iter<'h, phr_header>,
+    _buf: PhantomData<&'buf [u8]>,
+}
+
+impl<'buf, 'h> Iterator for Iter<'buf, 'h> {
+    type Item = Header<'buf>;
+
+    fn next(&mut self) -> Option<Header<'buf>> {
+        self.inner.next().map(|raw| unsafe { Header::from_raw(raw) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'buf, 'h> DoubleEndedIterator for Iter<'buf, 'h> {
+    fn next_back(&mut self) -> Option<Header<'buf>> {
+        self.inner.next_back().map(|raw| unsafe { Header::from_raw(raw) })
+    }
+}
+
+impl<'buf, 'h> ExactSizeIterator for Iter<'buf, 'h> {}
+
+/// A header field together with any continuation lines that fold its value.
+#[derive(Clone, Copy)]
+pub struct Field<'buf, 'h> {
+    raw: &'h [phr_header],
+    _buf: PhantomData<&'buf [u8]>,
+}
+
+impl<'buf, 'h> Field<'buf, 'h> {
+    pub fn name(&self) -> &'buf [u8] {
+        unsafe { Header::from_raw(&self.raw[0]).name() }
+    }
+
+    /// Returns `true` if the value continues on further lines.
+    pub fn is_folded(&self) -> bool {
+        self.raw.len() > 1
+    }
+
+    /// The value of each line as received, starting with the header line
+    /// itself. Continuation lines keep their leading whitespace.
+    pub fn segments(&self) -> Segments<'buf, 'h> {
+        Segments {
+            inner: unsafe { Headers::from_raw(self.raw) }.iter(),
+        }
+    }
+
+    /// The logical value, with each fold replaced by a single space.
+    ///
+    /// Only allocates if the value is folded.
+    pub fn value(&self) -> Cow<'buf, [u8]> {
+        let mut segments = self.segments();
+        let first = segments.next().unwrap_or(&[]);
+        if !self.is_folded() {
+            return Cow::Borrowed(first);
+        }
+
+        let mut value = first.to_vec();
+        for segment in segments {
+            let segment = trim_start(segment);
+            if segment.is_empty() {
+                continue;
+            }
+            let len = trim_end(&value).len();
+            value.truncate(len);
+            if !value.is_empty() {
+                value.push(b' ');
+            }
+            value.extend_from_slice(segment);
+        }
+        Cow::Owned(value)
+    }
+}
+
+impl<'buf, 'h> fmt::Debug for Field<'buf, 'h> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name())
+            .field("segments", &self.segments().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+// tchar from RFC 9110 section 5.6.2.
+fn is_token_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+fn trim_start(mut bytes: &[u8]) -> &[u8] {
+    while let Some((&b' ', rest)) | Some((&b'\t', rest)) = bytes.split_first() {
+        bytes = rest;
+    }
+    bytes
+}
+
+fn trim_end(mut bytes: &[u8]) -> &[u8] {
+    while let Some((&b' ', rest)) | Some((&b'\t', rest)) = bytes.split_last() {
+        bytes = rest;
+    }
+    bytes
+}
+
+/// Iterator over header fields, returned by `Headers::fields`.
+#[derive(Clone)]
+pub struct Fields<'buf, 'h> {
+    raw: &'h [phr_header],
+    _buf: PhantomData<&'buf [u8]>,
+}
+
+impl<'buf, 'h> Iterator for Fields<'buf, 'h> {
+    type Item = Field<'buf, 'h>;
+
+    fn next(&mut self) -> Option<Field<'buf, 'h>> {
+        if self.raw.is_empty() {
+            return None;
+        }
+        let len = 1 + self.raw[1..].iter().take_while(|raw| raw.name.is_null()).count();
+        let (field, rest) = self.raw.split_at(len);
+        self.raw = rest;
+        Some(Field {
+            raw: field,
+            _buf: PhantomData,
+        })
+    }
+}
+
+/// Iterator over the line values of a `Field`.
+#[derive(Clone)]
+pub struct Segments<'buf, 'h> {
+    inner: Iter<'buf, 'h>,
+}
+
+impl<'buf, 'h> Iterator for Segments<'buf, 'h> {
+    type Item = &'buf [u8];
+
+    fn next(&mut self) -> Option<&'buf [u8]> {
+        self.inner.next().map(|header| header.value())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'buf, 'h> ExactSizeIterator for Segments<'buf, 'h> {}
+
+/// Iterator over the values of the headers with a given name, returned by
+/// `Headers::get_all`.
+#[derive(Clone)]
+pub struct GetAll<'buf, 'h, 'n> {
+    inner: Iter<'buf, 'h>,
+    name: &'n [u8],
+}
+
+impl<'buf, 'h, 'n> Iterator for GetAll<'buf, 'h, 'n> {
+    type Item = &'buf [u8];
+
+    fn next(&mut self) -> Option<&'buf [u8]> {
+        let name = self.name;
+        self.inner
+            .by_ref()
+            .find(|header| !header.is_continuation() && header.name().eq_ignore_ascii_case(name))
+            .map(|header| header.value())
+    }
+}
+
+const INLINE_HEADERS: usize = 32;
+const DEFAULT_MAX_HEADERS: usize = 100;
+
+/// Header storage for the `parse_growing` entry points.
+///
+/// Parsing starts with an inline array of 32 headers and moves to a heap
+/// allocated `Vec` when a head has more headers than that, up to
+/// `max_headers`. The heap storage is kept for reuse by later parses.
+#[derive(Clone)]
+pub struct HeaderBuffer {
+    inline: [phr_header; INLINE_HEADERS],
+    heap: Vec<phr_header>,
+    max_headers: usize,
+}
+
+impl HeaderBuffer {
+    /// Creates storage for up to 100 headers.
+    pub fn new() -> HeaderBuffer {
+        HeaderBuffer::with_max_headers(DEFAULT_MAX_HEADERS)
+    }
+
+    /// Creates storage for up to `max_headers` headers.
+    pub fn with_max_headers(max_headers: usize) -> HeaderBuffer {
+        HeaderBuffer {
+            inline: [phr_header::default(); INLINE_HEADERS],
+            heap: Vec::new(),
+            max_headers,
+        }
+    }
+
+    pub fn max_headers(&self) -> usize {
+        self.max_headers
+    }
+
+    // Calls `parse` with the inline storage and, if the head turns out to
+    // have more headers than that but no more than `max_headers`, again with
+    // heap storage sized to fit.
+    fn parse_with<'h, T, F>(&'h mut self, mut parse: F) -> Result<Status<T>, ParseError>
+        where F: FnMut(&'h mut [phr_header]) -> Result<Status<T>, ParseError>
+    {
+        let HeaderBuffer { ref mut inline, ref mut heap, max_headers } = *self;
+        let inline_len = cmp::min(inline.len(), max_headers);
+
+        match parse(&mut inline[..inline_len]) {
+            Err(ParseError::TooManyHeaders { needed }) if needed <= max_headers => {
+                heap.clear();
+                heap.resize(needed, phr_header::default());
+                parse(&mut heap[..])
+            }
+            result => result,
+        }
+    }
+}
+
+impl Default for HeaderBuffer {
+    fn default() -> HeaderBuffer {
+        HeaderBuffer::new()
+    }
+}
+
+impl fmt::Debug for HeaderBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeaderBuffer")
+            .field("heap_capacity", &self.heap.capacity())
+            .field("max_headers", &self.max_headers)
+            .finish()
+    }
+}
+
+/// A request head parsed by `phr_parse_request`.
+#[derive(Clone, Copy, Debug)]
+pub struct Request<'buf, 'h> {
+    method: &'buf [u8],
+    path: &'buf [u8],
+    minor_version: u8,
+    headers: Headers<'buf, 'h>,
+}
+
+impl<'buf, 'h> Request<'buf, 'h> {
+    /// Parses a request head from `buf`, storing up to `headers.len()` headers.
+    pub fn parse(buf: &'buf [u8],
+                 headers: &'h mut [phr_header])
+                 -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        Request::parse_with_last_len(buf, 0, headers)
+    }
+
+    /// Like `parse`, but retries with more header storage from `headers`
+    /// when needed.
+    pub fn parse_growing(buf: &'buf [u8],
+                         headers: &'h mut HeaderBuffer)
+                         -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| Request::parse(buf, headers))
+    }
+
+    /// Like `parse`, but only rescans from `last_len`, the length of `buf`
+    /// at the previous partial attempt.
+    pub fn parse_with_last_len(buf: &'buf [u8],
+                               last_len: usize,
+                               headers: &'h mut [phr_header])
+                               -> Result<Status<Request<'buf, 'h>>, ParseError> {
+        match Request::parse_detached(buf, last_len, headers)? {
+            Status::Complete(consumed, (mut req, num_headers)) => {
+                let headers: &'h [phr_header] = headers;
+                req.headers = unsafe { Headers::from_raw(&headers[..num_headers]) };
+                Ok(Status::Complete(consumed, req))
+            }
+            Status::Partial => Ok(Status::Partial),
+        }
+    }
+
+    // Parses into `headers` without holding on to the borrow, returning the
+    // request with no headers attached along with the number of headers
+    // filled in.
+    fn parse_detached(buf: &'buf [u8],
+                      last_len: usize,
+                      headers: &mut [phr_header])
+                      -> Result<Status<(Request<'buf, 'h>, usize)>, ParseError> {
+        let mut method = ptr::null();
+        let mut method_len = 0;
+        let mut path = ptr::null();
+        let mut path_len = 0;
+        let mut minor_version = -1;
+
+        let parsed = parse_with(buf, headers, |headers, num_headers| unsafe {
+            phr_parse_request(buf.as_ptr() as *const c_char,
+                              buf.len(),
+                              &mut method,
+                              &mut method_len,
+                              &mut path,
+                              &mut path_len,
+                              &mut minor_version,
+                              headers.as_mut_ptr(),
+                              num_headers,
+                              last_len)
+        })?;
+        let (consumed, num_headers) = match parsed {
+            Some(parsed) => parsed,
+            None => return Ok(Status::Partial),
+        };
+        let minor_version = minor_version_from_raw(minor_version)?;
+
+        unsafe {
+            Ok(Status::Complete(consumed,
+                                (Request {
+                                    method: slice_from_raw(method, method_len),
+                                    path: slice_from_raw(path, path_len),
+                                    minor_version,
+                                    headers: Headers::from_raw(&[]),
+                                },
+                                 num_headers)))
+        }
+    }
+
+    pub fn method(&self) -> Method<'buf> {
+        Method::from_bytes(self.method)
+    }
+
+    /// The method as sent.
+    pub fn method_bytes(&self) -> &'buf [u8] {
+        self.method
+    }
+
+    pub fn path(&self) -> &'buf [u8] {
+        self.path
+    }
+
+    /// The query string of the path.
+    pub fn query(&self) -> Query<'buf> {
+        Query::from_path(self.path)
+    }
+
+    /// Classifies the request-target, see `RequestTarget::parse`.
+    pub fn target(&self) -> Result<RequestTarget<'buf>, ParseError> {
+        RequestTarget::parse(self.path, self.method())
+    }
+
+    pub fn minor_version(&self) -> u8 {
+        self.minor_version
+    }
+
+    pub fn headers(&self) -> Headers<'buf, 'h> {
+        self.headers
+    }
+}
+
+/// Accumulates a request head that arrives in pieces.
+///
+/// The parser keeps track of how much of its buffer was already scanned and
+/// passes that along as `last_len`, so each `feed` only looks at the new
+/// bytes until the end of the head shows up. This is picohttpparser's
+/// countermeasure against slowloris-style clients.
+#[derive(Clone, Debug, Default)]
+pub struct RequestHeadParser {
+    buf: Vec<u8>,
+    last_len: usize,
+    strict_checked: usize,
+    config: ParserConfig,
+}
+
+impl RequestHeadParser {
+    pub fn new() -> RequestHeadParser {
+        RequestHeadParser::default()
+    }
+
+    /// Creates a parser that applies the limits and strict checks of
+    /// `config`. The head and request-target limits are checked on every
+    /// `feed`, so a client cannot make the buffer grow much past them.
+    pub fn with_config(config: ParserConfig) -> RequestHeadParser {
+        RequestHeadParser {
+            config,
+            ..RequestHeadParser::default()
+        }
+    }
+
+    /// Appends `data` to the buffer and tries to parse the request head.
+    ///
+    /// Once complete, the bytes after the consumed head (the start of the
+    /// body or of the next request) remain in `buffer()`.
+    pub fn feed<'a, 'h>(&'a mut self,
+                        data: &[u8],
+                        headers: &'h mut [phr_header])
+                        -> Result<Status<Request<'a, 'h>>, ParseError> {
+        self.buf.extend_from_slice(data);
+
+        match self.config.parse_request_resuming(&self.buf,
+                                                 self.last_len,
+                                                 &mut self.strict_checked,
+                                                 headers)? {
+            Status::Partial => {
+                self.last_len = self.buf.len();
+                Ok(Status::Partial)
+            }
+            complete => Ok(complete),
+        }
+    }
+
+    /// The bytes fed so far that have not been consumed.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Drops the first `n` bytes of the buffer, typically the head returned
+    /// by `feed`, and starts scanning the rest afresh.
+    pub fn consume(&mut self, n: usize) {
+        self.buf.drain(..n);
+        self.last_len = 0;
+        self.strict_checked = 0;
+    }
+
+    /// Clears the buffer so the parser can be reused for another request.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.last_len = 0;
+        self.strict_checked = 0;
+    }
+
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// A response head parsed by `phr_parse_response`.
+#[derive(Clone, Copy, Debug)]
+pub struct Response<'buf, 'h> {
+    minor_version: u8,
+    status: u16,
+    reason: &'buf [u8],
+    headers: Headers<'buf, 'h>,
+}
+
+impl<'buf, 'h> Response<'buf, 'h> {
+    /// Parses a response head from `buf`, storing up to `headers.len()`
+    /// headers.
+    pub fn parse(buf: &'buf [u8],
+                 headers: &'h mut [phr_header])
+                 -> Result<Status<Response<'buf, 'h>>, ParseError> {
+        Response::parse_with_last_len(buf, 0, headers)
+    }
+
+    /// Like `parse`, but retries with more header storage from `headers`
+    /// when needed.
+    pub fn parse_growing(buf: &'buf [u8],
+                         headers: &'h mut HeaderBuffer)
+                         -> Result<Status<Response<'buf, 'h>>, ParseError> {
+        headers.parse_with(|headers| Response::parse(buf, headers))
+    }
+
+    /// Like `parse`, but only rescans from `last_len`, the length of `buf`
+    /// at the previous partial attempt.
+    pub fn parse_with_last_len(buf: &'buf [u8],
+                               last_len: usize,
+                               headers: &'h mut [phr_header])
+                               -> Result<Status<Response<'buf, 'h>>, ParseError> {
+        let mut minor_version = -1;
+        let mut status = 0;
+        let mut reason = ptr::null();
+        let mut reason_len = 0;
+
+        let parsed = parse_with(buf, headers, |headers, num_headers| unsafe {
+            phr_parse_response(buf.as_ptr() as *const c_char,
+                               buf.len(),
+                               &mut minor_version,
+                               &mut status,
+                               &mut reason,
+                               &mut reason_len,
+                               headers.as_mut_ptr(),
+                               num_headers,
+                               last_len)
+        })?;
+        let (consumed, num_headers) = match parsed {
+            Some(parsed) => parsed,
+            None => return Ok(Status::Partial),
+        };
+        let minor_version = minor_version_from_raw(minor_version)?;
+        if status < 0 || status > u16::MAX as c_int {
+            return Err(ParseError::StatusCode);
+        }
+
+        let headers: &'h [phr_header] = headers;
+        unsafe {
+            Ok(Status::Complete(consumed,
+                                Response {
+                                    minor_version,
+                                    status: status as u16,
+                                    reason: slice_from_raw(reason, reason_len),
+                                    headers: Headers::from_raw(&headers[..num_headers]),
+                                }))
+        }
+    }
+
+    pub fn minor_version(&self) -> u8 {
+        self.minor_version
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn reason(&self) -> &'buf [u8] {
+        self.reason
+    }
+
+    pub fn headers(&self) -> Headers<'buf, 'h> {
+        self.headers
+    }
+}
+
+/// Result of a `ChunkedDecoder::decode` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkedStatus {
+    /// The end of the chunked body was found. The first `decoded` bytes of
+    /// the buffer hold payload and the `remaining` bytes after them are the
+    /// undecoded data that followed the body.
+    Complete { decoded: usize, remaining: usize },
+    /// More data is needed. The first `decoded` bytes of the buffer hold
+    /// payload.
+    Partial { decoded: usize },
+}
+
+/// Stateful decoder for `Transfer-Encoding: chunked` bodies, driving
+/// `phr_decode_chunked`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChunkedDecoder {
+    raw: phr_chunked_decoder,
+}
+
+impl ChunkedDecoder {
+    /// Creates a decoder. If `consume_trailer` is set, the trailer section
+    /// after the last chunk is skipped as part of the body.
+    pub fn new(consume_trailer: bool) -> ChunkedDecoder {
+        ChunkedDecoder {
+            raw: phr_chunked_decoder {
+                consume_trailer: consume_trailer as c_char,
+                ..phr_chunked_decoder::default()
+            },
+        }
+    }
+
+    /// Decodes `buf` in place, moving the payload to the front of the buffer.
+    ///
+    /// Call repeatedly with newly arrived data while the status is `Partial`.
+    pub fn decode(&mut self, buf: &mut [u8]) -> Result<ChunkedStatus, ParseError> {
+        let mut bufsz = buf.len();
+        let ret = unsafe {
+            phr_decode_chunked(&mut self.raw, buf.as_mut_ptr() as *mut c_char, &mut bufsz)
+        };
+
+        match ret {
+            -2 => Ok(ChunkedStatus::Partial { decoded: bufsz }),
+            ret if ret < 0 => Err(ParseError::ChunkedEncoding),
+            ret => {
+                Ok(ChunkedStatus::Complete {
+                    decoded: bufsz,
+                    remaining: ret as usize,
+                })
+            }
+        }
+    }
+
+    /// Decodes `input` without modifying it, appending the payload to `out`.
+    ///
+    /// The status counts the bytes appended to `out` as `decoded`, and
+    /// `remaining` is the number of bytes at the end of `input` that follow
+    /// the body. Otherwise this behaves exactly like `decode`, and the two
+    /// can be mixed on the same decoder.
+    pub fn decode_into(&mut self,
+                       input: &[u8],
+                       out: &mut Vec<u8>)
+                       -> Result<ChunkedStatus, ParseError> {
+        let start = out.len();
+        out.extend_from_slice(input);
+        let status = self.decode(&mut out[start..]);
+        let end = match status {
+            Ok(ChunkedStatus::Complete { decoded, .. }) |
+            Ok(ChunkedStatus::Partial { decoded }) => start + decoded,
+            Err(_) => start,
+        };
+        out.truncate(end);
+        status
+    }
+
+    /// Walks `buf` and yields the chunk data in it as slices of `buf`,
+    /// without moving anything.
+    ///
+    /// The decoder's state advances just as with `decode`, so the next call
+    /// can pick up where the iterator stopped.
+    pub fn slices<'d, 'buf>(&'d mut self, buf: &'buf [u8]) -> ChunkSlices<'d, 'buf> {
+        ChunkSlices::new(self, buf)
+    }
+
+    /// Parses the trailer section that follows the last chunk.
+    ///
+    /// A decoder created with `consume_trailer` unset stops right after the
+    /// last chunk, so the `remaining` bytes of a `Complete` status start with
+    /// the trailer fields. Pass them here; the bytes consumed include the
+    /// empty line ending the body, and anything after that belongs to the
+    /// next message. On `Partial`, append more data to the tail and retry.
+    pub fn parse_trailers<'buf, 'h>(tail: &'buf [u8],
+                                    headers: &'h mut [phr_header])
+                                    -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        Headers::parse(tail, headers)
+    }
+
+    /// Like `parse_trailers`, but retries with more header storage from
+    /// `headers` when needed.
+    pub fn parse_trailers_growing<'buf, 'h>(tail: &'buf [u8],
+                                            headers: &'h mut HeaderBuffer)
+                                            -> Result<Status<Headers<'buf, 'h>>, ParseError> {
+        Headers::parse_growing(tail, headers)
+    }
+
+    /// Returns `true` if the decoder is in the middle of chunk data.
+    pub fn is_in_data(&self) -> bool {
+        let mut raw = self.raw;
+        unsafe { phr_decode_chunked_is_in_data(&mut raw) != 0 }
+    }
+}
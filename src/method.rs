@@ -0,0 +1,111 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A request method, as defined in RFC 9110 section 9 and RFC 5789.
+///
+/// Methods are case-sensitive, so `get` is an extension method rather than
+/// `Get`. Methods are compared by name, so `Extension(b"HEAD")` is treated
+/// exactly like `Head`.
+#[derive(Clone, Copy, Debug)]
+pub enum Method<'buf> {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Connect,
+    Options,
+    Trace,
+    Patch,
+    /// Any other method.
+    Extension(&'buf [u8]),
+}
+
+impl<'buf> Method<'buf> {
+    pub fn from_bytes(method: &'buf [u8]) -> Method<'buf> {
+        match method {
+            b"GET" => Method::Get,
+            b"HEAD" => Method::Head,
+            b"POST" => Method::Post,
+            b"PUT" => Method::Put,
+            b"DELETE" => Method::Delete,
+            b"CONNECT" => Method::Connect,
+            b"OPTIONS" => Method::Options,
+            b"TRACE" => Method::Trace,
+            b"PATCH" => Method::Patch,
+            method => Method::Extension(method),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'buf [u8] {
+        match *self {
+            Method::Get => b"GET",
+            Method::Head => b"HEAD",
+            Method::Post => b"POST",
+            Method::Put => b"PUT",
+            Method::Delete => b"DELETE",
+            Method::Connect => b"CONNECT",
+            Method::Options => b"OPTIONS",
+            Method::Trace => b"TRACE",
+            Method::Patch => b"PATCH",
+            Method::Extension(method) => method,
+        }
+    }
+
+    /// Whether the method is read-only (RFC 9110 section 9.2.1). Extension
+    /// methods are assumed not to be.
+    pub fn is_safe(&self) -> bool {
+        match self.normalize() {
+            Method::Get | Method::Head | Method::Options | Method::Trace => true,
+            Method::Post | Method::Put | Method::Delete | Method::Connect | Method::Patch |
+            Method::Extension(_) => false,
+        }
+    }
+
+    /// Whether repeating the request has the same effect as sending it once
+    /// (RFC 9110 section 9.2.2). Extension methods are assumed not to be.
+    pub fn is_idempotent(&self) -> bool {
+        self.is_safe() || *self == Method::Put || *self == Method::Delete
+    }
+
+    /// Whether request content has a defined meaning for the method.
+    ///
+    /// Content in a TRACE request is forbidden, and for GET, HEAD, DELETE and
+    /// CONNECT it has no defined semantics and may be rejected. Extension
+    /// methods are assumed to allow it.
+    pub fn allows_request_body(&self) -> bool {
+        match self.normalize() {
+            Method::Get | Method::Head | Method::Delete | Method::Connect | Method::Trace => false,
+            Method::Post | Method::Put | Method::Options | Method::Patch |
+            Method::Extension(_) => true,
+        }
+    }
+
+    // Turns an `Extension` holding a registered name into its variant.
+    fn normalize(&self) -> Method<'buf> {
+        match *self {
+            Method::Extension(method) => Method::from_bytes(method),
+            method => method,
+        }
+    }
+}
+
+impl<'buf, 'other> PartialEq<Method<'other>> for Method<'buf> {
+    fn eq(&self, other: &Method<'other>) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<'buf> Eq for Method<'buf> {}
+
+impl<'buf> Hash for Method<'buf> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<'buf> fmt::Display for Method<'buf> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(self.as_bytes()))
+    }
+}
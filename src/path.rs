@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+
+use query::hex_value;
+
+/// Something unusual found while normalizing a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathAnomaly {
+    /// The path does not start with `/`. One was added.
+    NotAbsolute,
+    /// A `.` or `..` segment was removed.
+    DotSegment,
+    /// Consecutive slashes were collapsed into one.
+    DuplicateSlash,
+    /// A backslash was treated as a separator and replaced with `/`.
+    Backslash,
+    /// A `..` segment would have gone above the root. It was dropped.
+    TraversalAboveRoot,
+    /// A slash or backslash is percent-encoded (`%2F` or `%5C`). It was
+    /// left encoded so that it cannot split a segment.
+    EncodedSlash,
+    /// The path contains a NUL byte, raw or as `%00`. It was left encoded.
+    Nul,
+    /// A `%` is not followed by two hex digits. It was encoded as `%25`.
+    InvalidEscape,
+}
+
+impl PathAnomaly {
+    /// Whether a path with this anomaly should be refused outright rather
+    /// than served under its normalized form.
+    pub fn is_rejected(&self) -> bool {
+        match *self {
+            PathAnomaly::TraversalAboveRoot | PathAnomaly::EncodedSlash | PathAnomaly::Nul => true,
+            PathAnomaly::NotAbsolute | PathAnomaly::DotSegment | PathAnomaly::DuplicateSlash |
+            PathAnomaly::Backslash | PathAnomaly::InvalidEscape => false,
+        }
+    }
+}
+
+/// The result of `normalize_path`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizedPath<'buf> {
+    path: Cow<'buf, [u8]>,
+    anomalies: Vec<PathAnomaly>,
+}
+
+impl<'buf> NormalizedPath<'buf> {
+    pub fn path(&self) -> &[u8] {
+        &self.path
+    }
+
+    pub fn into_path(self) -> Cow<'buf, [u8]> {
+        self.path
+    }
+
+    /// The anomalies found, each listed once in the order first seen.
+    pub fn anomalies(&self) -> &[PathAnomaly] {
+        &self.anomalies
+    }
+
+    /// Returns `true` if none of the anomalies is one that `is_rejected`.
+    pub fn is_safe(&self) -> bool {
+        !self.anomalies.iter().any(PathAnomaly::is_rejected)
+    }
+}
+
+/// Normalizes a request path for mapping it to files or routes.
+///
+/// Percent-escapes are decoded, dot-segments are removed and duplicate
+/// slashes are collapsed, in that order, so an encoded `%2e%2e` is treated
+/// like `..`. Backslashes separate segments like slashes do. Escapes that
+/// would produce a separator, a NUL or a `%` are left encoded, so decoding
+/// the result again cannot introduce any of them. Anything from `?` on is
+/// ignored; see `Query` for that part. The result only allocates if it
+/// differs from the input.
+pub fn normalize_path<'buf>(path: &'buf [u8]) -> NormalizedPath<'buf> {
+    let path = match path.iter().position(|&b| b == b'?') {
+        Some(end) => &path[..end],
+        None => path,
+    };
+    let mut anomalies = Vec::new();
+    let mut report = |anomaly| {
+        if !anomalies.contains(&anomaly) {
+            anomalies.push(anomaly);
+        }
+    };
+
+    let relative = match path.split_first() {
+        Some((&b'/', rest)) => rest,
+        _ => {
+            report(PathAnomaly::NotAbsolute);
+            path
+        }
+    };
+
+    if relative.contains(&b'\\') {
+        report(PathAnomaly::Backslash);
+    }
+
+    let mut segments: Vec<Cow<[u8]>> = Vec::new();
+    let mut trailing_slash = false;
+    let raw_segments: Vec<&[u8]> = relative.split(|&b| b == b'/' || b == b'\\').collect();
+    for (i, raw) in raw_segments.iter().enumerate() {
+        let is_last = i == raw_segments.len() - 1;
+        let segment = decode_segment(raw, &mut report);
+        trailing_slash = false;
+        match &segment[..] {
+            b"" if is_last => trailing_slash = !segments.is_empty(),
+            b"" => report(PathAnomaly::DuplicateSlash),
+            b"." => {
+                report(PathAnomaly::DotSegment);
+                trailing_slash = is_last && !segments.is_empty();
+            }
+            b".." => {
+                if segments.pop().is_some() {
+                    report(PathAnomaly::DotSegment);
+                } else {
+                    report(PathAnomaly::TraversalAboveRoot);
+                }
+                trailing_slash = is_last && !segments.is_empty();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut normalized = Vec::with_capacity(path.len() + 1);
+    for segment in &segments {
+        normalized.push(b'/');
+        normalized.extend_from_slice(segment);
+    }
+    if trailing_slash || segments.is_empty() {
+        normalized.push(b'/');
+    }
+
+    NormalizedPath {
+        path: if normalized == path {
+            Cow::Borrowed(path)
+        } else {
+            Cow::Owned(normalized)
+        },
+        anomalies,
+    }
+}
+
+// Decodes the percent-escapes of a single segment, except those that would
+// introduce a separator, a NUL or a `%`. A `%` that does not start an escape
+// is encoded, so every `%` in the result starts one.
+fn decode_segment<'a, F>(raw: &'a [u8], report: &mut F) -> Cow<'a, [u8]>
+    where F: FnMut(PathAnomaly)
+{
+    if !raw.iter().any(|&b| b == b'%' || b == 0) {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let b = raw[i];
+        if b == 0 {
+            report(PathAnomaly::Nul);
+            decoded.extend_from_slice(b"%00");
+            i += 1;
+            continue;
+        }
+        if b != b'%' {
+            decoded.push(b);
+            i += 1;
+            continue;
+        }
+        match (raw.get(i + 1).and_then(hex_value), raw.get(i + 2).and_then(hex_value)) {
+            (Some(high), Some(low)) => {
+                match high << 4 | low {
+                    b'/' | b'\\' => {
+                        report(PathAnomaly::EncodedSlash);
+                        decoded.extend_from_slice(&raw[i..i + 3]);
+                    }
+                    0 => {
+                        report(PathAnomaly::Nul);
+                        decoded.extend_from_slice(&raw[i..i + 3]);
+                    }
+                    b'%' => decoded.extend_from_slice(&raw[i..i + 3]),
+                    b => decoded.push(b),
+                }
+                i += 3;
+            }
+            _ => {
+                report(PathAnomaly::InvalidEscape);
+                decoded.extend_from_slice(b"%25");
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(decoded)
+}
@@ -0,0 +1,151 @@
+use std::mem;
+
+use super::{phr_header, BodyFraming, ChunkedDecoder, Headers, ParseError, Request, Status};
+
+/// Iterator over requests sent back to back in a single buffer.
+///
+/// Each item is a request together with its body framing and the raw bytes
+/// of its body. Iteration stops at the end of the buffer or at the first
+/// request that is not complete yet; `offset` then tells where that request
+/// starts. After an error the iterator yields nothing more.
+///
+/// The header storage is shared by all requests yielded, each one taking
+/// the slots it needs from what is left, so it has to be large enough for
+/// the whole pipeline. When it runs out, `ParseError::TooManyHeaders` gives
+/// the number of slots needed from the start of the buffer.
+pub struct Pipeline<'buf, 'h> {
+    buf: &'buf [u8],
+    offset: usize,
+    headers: &'h mut [phr_header],
+    headers_used: usize,
+    failed: bool,
+}
+
+impl<'buf, 'h> Pipeline<'buf, 'h> {
+    pub fn new(buf: &'buf [u8], headers: &'h mut [phr_header]) -> Pipeline<'buf, 'h> {
+        Pipeline {
+            buf,
+            offset: 0,
+            headers,
+            headers_used: 0,
+            failed: false,
+        }
+    }
+
+    /// The number of bytes taken up by the requests yielded so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes after the requests yielded so far: an incomplete request,
+    /// or nothing if the buffer ended on a request boundary.
+    pub fn remainder(&self) -> &'buf [u8] {
+        &self.buf[self.offset..]
+    }
+
+    fn next_request(&mut self) -> Result<Option<Pipelined<'buf, 'h>>, ParseError> {
+        let buf = &self.buf[self.offset..];
+        let (head_len, mut request, num_headers) =
+            match Request::parse_detached(buf, 0, self.headers).map_err(|e| self.cumulative(e))? {
+                Status::Complete(head_len, (request, num_headers)) => {
+                    (head_len, request, num_headers)
+                }
+                Status::Partial => return Ok(None),
+            };
+
+        let framing = BodyFraming::for_request(&Request {
+            headers: unsafe { Headers::from_raw(&self.headers[..num_headers]) },
+            ..request
+        })?;
+        let body = &buf[head_len..];
+        let body_len = match framing {
+            BodyFraming::Empty => 0,
+            BodyFraming::ContentLength(len) if len <= body.len() as u64 => len as usize,
+            BodyFraming::ContentLength(_) => return Ok(None),
+            BodyFraming::Chunked => {
+                match chunked_len(body)? {
+                    Some(len) => len,
+                    None => return Ok(None),
+                }
+            }
+            BodyFraming::CloseDelimited => unreachable!("requests are never close-delimited"),
+        };
+
+        let (used, rest) = mem::take(&mut self.headers).split_at_mut(num_headers);
+        let used: &'h [phr_header] = used;
+        request.headers = unsafe { Headers::from_raw(used) };
+        self.headers = rest;
+        self.headers_used += num_headers;
+        self.offset += head_len + body_len;
+        Ok(Some(Pipelined {
+            request,
+            framing,
+            body: &body[..body_len],
+        }))
+    }
+
+    // Counts the slots taken by earlier requests in a `TooManyHeaders`.
+    fn cumulative(&self, e: ParseError) -> ParseError {
+        match e {
+            ParseError::TooManyHeaders { needed } => {
+                ParseError::TooManyHeaders { needed: self.headers_used + needed }
+            }
+            e => e,
+        }
+    }
+}
+
+impl<'buf, 'h> Iterator for Pipeline<'buf, 'h> {
+    type Item = Result<Pipelined<'buf, 'h>, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Pipelined<'buf, 'h>, ParseError>> {
+        if self.failed || self.offset == self.buf.len() {
+            return None;
+        }
+        match self.next_request() {
+            Ok(pipelined) => pipelined.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// Returns the length of the chunked body at the start of `buf`, including
+// its trailer section, or `None` if it is not complete.
+fn chunked_len(buf: &[u8]) -> Result<Option<usize>, ParseError> {
+    let mut decoder = ChunkedDecoder::new(true);
+    let mut slices = decoder.slices(buf);
+    for slice in &mut slices {
+        slice?;
+    }
+    Ok(if slices.is_complete() {
+        Some(slices.offset())
+    } else {
+        None
+    })
+}
+
+/// A request yielded by `Pipeline`.
+#[derive(Clone, Copy, Debug)]
+pub struct Pipelined<'buf, 'h> {
+    request: Request<'buf, 'h>,
+    framing: BodyFraming,
+    body: &'buf [u8],
+}
+
+impl<'buf, 'h> Pipelined<'buf, 'h> {
+    pub fn request(&self) -> Request<'buf, 'h> {
+        self.request
+    }
+
+    pub fn framing(&self) -> BodyFraming {
+        self.framing
+    }
+
+    /// The body as sent, still chunked if the framing is `Chunked`.
+    pub fn body(&self) -> &'buf [u8] {
+        self.body
+    }
+}
@@ -0,0 +1,180 @@
+use std::borrow::Cow;
+
+/// The query string of a request-target, split into `key=value` pairs.
+///
+/// Keys and values are only percent-decoded, with `+` as space, when asked
+/// for. Pairs are separated by `&`, empty pairs are skipped, and a pair
+/// without `=` has an empty value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Query<'buf> {
+    raw: &'buf [u8],
+}
+
+impl<'buf> Query<'buf> {
+    /// Wraps a query string without the leading `?`.
+    pub fn new(raw: &'buf [u8]) -> Query<'buf> {
+        Query { raw }
+    }
+
+    /// Takes the query from a path as returned by `Request::path`, which is
+    /// empty if there is no `?`.
+    pub fn from_path(path: &'buf [u8]) -> Query<'buf> {
+        match path.iter().position(|&b| b == b'?') {
+            Some(i) => Query::new(&path[i + 1..]),
+            None => Query::new(b""),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'buf [u8] {
+        self.raw
+    }
+
+    pub fn iter(&self) -> Pairs<'buf> {
+        Pairs { rest: self.raw }
+    }
+
+    /// Returns the value of the first pair whose decoded key is `key`.
+    pub fn get(&self, key: &[u8]) -> Option<QueryPart<'buf>> {
+        self.get_all(key).next()
+    }
+
+    /// Returns the values of all pairs whose decoded key is `key`, in order.
+    pub fn get_all<'k>(&self, key: &'k [u8]) -> Values<'buf, 'k> {
+        Values {
+            pairs: self.iter(),
+            key,
+        }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<'buf> IntoIterator for Query<'buf> {
+    type Item = (QueryPart<'buf>, QueryPart<'buf>);
+    type IntoIter = Pairs<'buf>;
+
+    fn into_iter(self) -> Pairs<'buf> {
+        self.iter()
+    }
+}
+
+/// A key or value of a query string, still percent-encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryPart<'buf> {
+    raw: &'buf [u8],
+}
+
+impl<'buf> QueryPart<'buf> {
+    /// The part as sent.
+    pub fn as_bytes(&self) -> &'buf [u8] {
+        self.raw
+    }
+
+    /// Decodes percent-escapes and `+`. Only allocates if there is anything
+    /// to decode. Malformed escapes are kept as they are.
+    pub fn decode(&self) -> Cow<'buf, [u8]> {
+        if self.raw.iter().any(|&b| b == b'%' || b == b'+') {
+            Cow::Owned(self.decoded().collect())
+        } else {
+            Cow::Borrowed(self.raw)
+        }
+    }
+
+    /// Like `decode`, but also replaces invalid UTF-8.
+    pub fn decode_utf8_lossy(&self) -> Cow<'buf, str> {
+        match self.decode() {
+            Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+
+    /// Compares the decoded part with `other` without allocating.
+    pub fn decodes_to(&self, other: &[u8]) -> bool {
+        self.decoded().eq(other.iter().cloned())
+    }
+
+    fn decoded(&self) -> Decoded<'buf> {
+        Decoded { rest: self.raw }
+    }
+}
+
+/// Iterator over the pairs of a `Query`.
+#[derive(Clone, Debug)]
+pub struct Pairs<'buf> {
+    rest: &'buf [u8],
+}
+
+impl<'buf> Iterator for Pairs<'buf> {
+    type Item = (QueryPart<'buf>, QueryPart<'buf>);
+
+    fn next(&mut self) -> Option<(QueryPart<'buf>, QueryPart<'buf>)> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let (pair, rest) = match self.rest.iter().position(|&b| b == b'&') {
+                Some(end) => (&self.rest[..end], &self.rest[end + 1..]),
+                None => (self.rest, &b""[..]),
+            };
+            self.rest = rest;
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = match pair.iter().position(|&b| b == b'=') {
+                Some(eq) => (&pair[..eq], &pair[eq + 1..]),
+                None => (pair, &b""[..]),
+            };
+            return Some((QueryPart { raw: key }, QueryPart { raw: value }));
+        }
+    }
+}
+
+/// Iterator over the values of the pairs with a given key, created by
+/// `Query::get_all`.
+#[derive(Clone, Debug)]
+pub struct Values<'buf, 'k> {
+    pairs: Pairs<'buf>,
+    key: &'k [u8],
+}
+
+impl<'buf, 'k> Iterator for Values<'buf, 'k> {
+    type Item = QueryPart<'buf>;
+
+    fn next(&mut self) -> Option<QueryPart<'buf>> {
+        let key = self.key;
+        self.pairs.by_ref().find(|&(k, _)| k.decodes_to(key)).map(|(_, value)| value)
+    }
+}
+
+// Percent-decodes bytes one at a time, with `+` as space.
+struct Decoded<'buf> {
+    rest: &'buf [u8],
+}
+
+impl<'buf> Iterator for Decoded<'buf> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&b, rest) = self.rest.split_first()?;
+        self.rest = rest;
+        match b {
+            b'+' => Some(b' '),
+            b'%' => {
+                match (rest.first().and_then(hex_value), rest.get(1).and_then(hex_value)) {
+                    (Some(high), Some(low)) => {
+                        self.rest = &rest[2..];
+                        Some(high << 4 | low)
+                    }
+                    _ => Some(b'%'),
+                }
+            }
+            b => Some(b),
+        }
+    }
+}
+
+pub(crate) fn hex_value(b: &u8) -> Option<u8> {
+    (*b as char).to_digit(16).map(|v| v as u8)
+}
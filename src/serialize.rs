@@ -0,0 +1,410 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Write};
+
+use super::is_token_char;
+
+/// Why a message head could not be serialized.
+///
+/// Every field is checked so that the output parses back into exactly the
+/// values that were written, which rules out CR, LF and NUL anywhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializeError {
+    /// The method is empty or not a token.
+    Method,
+    /// The request-target is empty or contains whitespace or control
+    /// characters.
+    Target,
+    /// The minor version is not a single digit.
+    Version,
+    /// The status code does not have three digits.
+    StatusCode,
+    /// The reason phrase contains control characters or starts or ends with
+    /// whitespace.
+    Reason,
+    /// A header name is empty or not a token.
+    HeaderName,
+    /// A header value contains control characters or starts or ends with
+    /// whitespace.
+    HeaderValue,
+    /// A chunk extension name is not a token, or its value contains control
+    /// characters.
+    ChunkExtension,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            SerializeError::Method => "invalid method",
+            SerializeError::Target => "invalid request-target",
+            SerializeError::Version => "invalid minor version",
+            SerializeError::StatusCode => "invalid status code",
+            SerializeError::Reason => "invalid reason phrase",
+            SerializeError::HeaderName => "invalid header name",
+            SerializeError::HeaderValue => "invalid header value",
+            SerializeError::ChunkExtension => "invalid chunk extension",
+        })
+    }
+}
+
+impl error::Error for SerializeError {}
+
+impl From<SerializeError> for io::Error {
+    fn from(err: SerializeError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Appends a request head, including the empty line that ends it, to `out`.
+///
+/// Nothing is appended if any field is invalid.
+pub fn encode_request<I, N, V>(out: &mut Vec<u8>,
+                               method: &[u8],
+                               target: &[u8],
+                               minor_version: u8,
+                               headers: I)
+                               -> Result<(), SerializeError>
+    where I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    let start = out.len();
+    let result = encode_request_line(out, method, target, minor_version)
+        .and_then(|()| encode_headers(out, headers));
+    if result.is_err() {
+        out.truncate(start);
+    }
+    result
+}
+
+/// Writes a request head to `writer` with a single `write_all`.
+///
+/// Invalid fields are reported as `io::ErrorKind::InvalidInput` before
+/// anything is written.
+pub fn write_request<W, I, N, V>(writer: &mut W,
+                                 method: &[u8],
+                                 target: &[u8],
+                                 minor_version: u8,
+                                 headers: I)
+                                 -> io::Result<()>
+    where W: Write,
+          I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    let mut buf = Vec::new();
+    encode_request(&mut buf, method, target, minor_version, headers)?;
+    writer.write_all(&buf)
+}
+
+/// Appends a response head, including the empty line that ends it, to `out`.
+///
+/// Without a `reason`, the canonical phrase for `status` is used, or an
+/// empty one if there is none. Nothing is appended if any field is invalid.
+pub fn encode_response<I, N, V>(out: &mut Vec<u8>,
+                                minor_version: u8,
+                                status: u16,
+                                reason: Option<&[u8]>,
+                                headers: I)
+                                -> Result<(), SerializeError>
+    where I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    let start = out.len();
+    let result = encode_status_line(out, minor_version, status, reason)
+        .and_then(|()| encode_headers(out, headers));
+    if result.is_err() {
+        out.truncate(start);
+    }
+    result
+}
+
+/// Writes a response head to `writer` with a single `write_all`.
+///
+/// Invalid fields are reported as `io::ErrorKind::InvalidInput` before
+/// anything is written.
+pub fn write_response<W, I, N, V>(writer: &mut W,
+                                  minor_version: u8,
+                                  status: u16,
+                                  reason: Option<&[u8]>,
+                                  headers: I)
+                                  -> io::Result<()>
+    where W: Write,
+          I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    let mut buf = Vec::new();
+    encode_response(&mut buf, minor_version, status, reason, headers)?;
+    writer.write_all(&buf)
+}
+
+/// Returns the reason phrase registered for `status`, if any.
+pub fn reason_phrase(status: u16) -> Option<&'static str> {
+    Some(match status {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        102 => "Processing",
+        103 => "Early Hints",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        203 => "Non-Authoritative Information",
+        204 => "No Content",
+        205 => "Reset Content",
+        206 => "Partial Content",
+        207 => "Multi-Status",
+        208 => "Already Reported",
+        226 => "IM Used",
+        300 => "Multiple Choices",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        305 => "Use Proxy",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        407 => "Proxy Authentication Required",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Content Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        421 => "Misdirected Request",
+        422 => "Unprocessable Content",
+        423 => "Locked",
+        424 => "Failed Dependency",
+        425 => "Too Early",
+        426 => "Upgrade Required",
+        428 => "Precondition Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        451 => "Unavailable For Legal Reasons",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        506 => "Variant Also Negotiates",
+        507 => "Insufficient Storage",
+        508 => "Loop Detected",
+        511 => "Network Authentication Required",
+        _ => return None,
+    })
+}
+
+/// Writes a body with the chunked transfer coding.
+///
+/// Every `write` becomes one chunk. Use a `Vec<u8>` as the writer to build
+/// the encoded body in memory. The body is only complete once `finish` has
+/// written the last chunk; dropping the encoder does not do that.
+#[derive(Debug)]
+pub struct ChunkedEncoder<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ChunkedEncoder<W> {
+    pub fn new(writer: W) -> ChunkedEncoder<W> {
+        ChunkedEncoder { writer }
+    }
+
+    /// Writes `data` as a single chunk carrying the given chunk extensions.
+    ///
+    /// An extension with an empty value is written as a bare name, and values
+    /// that are not tokens are sent as quoted strings. Empty `data` writes
+    /// nothing, since an empty chunk would end the body.
+    pub fn write_chunk<I, N, V>(&mut self, data: &[u8], extensions: I) -> io::Result<()>
+        where I: IntoIterator<Item = (N, V)>,
+              N: AsRef<[u8]>,
+              V: AsRef<[u8]>
+    {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let mut head = format!("{:x}", data.len()).into_bytes();
+        encode_chunk_extensions(&mut head, extensions)?;
+        head.extend_from_slice(b"\r\n");
+        self.writer.write_all(&head)?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(b"\r\n")
+    }
+
+    /// Writes the last chunk and the trailer section, returning the writer.
+    pub fn finish<I, N, V>(mut self, trailers: I) -> io::Result<W>
+        where I: IntoIterator<Item = (N, V)>,
+              N: AsRef<[u8]>,
+              V: AsRef<[u8]>
+    {
+        let mut tail = b"0\r\n".to_vec();
+        encode_headers(&mut tail, trailers)?;
+        self.writer.write_all(&tail)?;
+        Ok(self.writer)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: Write> Write for ChunkedEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_chunk(buf, None::<(&[u8], &[u8])>)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// Writes each extension as ";name" or ";name=value".
+fn encode_chunk_extensions<I, N, V>(out: &mut Vec<u8>, extensions: I) -> Result<(), SerializeError>
+    where I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    for (name, value) in extensions {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        if name.is_empty() || !name.iter().all(|&b| is_token_char(b)) {
+            return Err(SerializeError::ChunkExtension);
+        }
+        out.push(b';');
+        out.extend_from_slice(name);
+        if value.is_empty() {
+            continue;
+        }
+        out.push(b'=');
+        if value.iter().all(|&b| is_token_char(b)) {
+            out.extend_from_slice(value);
+            continue;
+        }
+        if value.iter().any(|&b| b != b'\t' && is_ctl(b)) {
+            return Err(SerializeError::ChunkExtension);
+        }
+        out.push(b'"');
+        for &b in value {
+            if b == b'"' || b == b'\\' {
+                out.push(b'\\');
+            }
+            out.push(b);
+        }
+        out.push(b'"');
+    }
+    Ok(())
+}
+
+fn encode_request_line(out: &mut Vec<u8>,
+                       method: &[u8],
+                       target: &[u8],
+                       minor_version: u8)
+                       -> Result<(), SerializeError> {
+    if method.is_empty() || !method.iter().all(|&b| is_token_char(b)) {
+        return Err(SerializeError::Method);
+    }
+    if target.is_empty() || target.iter().any(|&b| b == b' ' || is_ctl(b)) {
+        return Err(SerializeError::Target);
+    }
+    out.extend_from_slice(method);
+    out.push(b' ');
+    out.extend_from_slice(target);
+    out.push(b' ');
+    encode_version(out, minor_version)?;
+    out.extend_from_slice(b"\r\n");
+    Ok(())
+}
+
+fn encode_status_line(out: &mut Vec<u8>,
+                      minor_version: u8,
+                      status: u16,
+                      reason: Option<&[u8]>)
+                      -> Result<(), SerializeError> {
+    if !(100..1000).contains(&status) {
+        return Err(SerializeError::StatusCode);
+    }
+    let reason = match reason {
+        Some(reason) => reason,
+        None => reason_phrase(status).unwrap_or("").as_bytes(),
+    };
+    if !is_field_text(reason) {
+        return Err(SerializeError::Reason);
+    }
+    encode_version(out, minor_version)?;
+    out.push(b' ');
+    out.push(b'0' + (status / 100) as u8);
+    out.push(b'0' + (status / 10 % 10) as u8);
+    out.push(b'0' + (status % 10) as u8);
+    out.push(b' ');
+    out.extend_from_slice(reason);
+    out.extend_from_slice(b"\r\n");
+    Ok(())
+}
+
+// Writes "HTTP/1.<minor_version>".
+fn encode_version(out: &mut Vec<u8>, minor_version: u8) -> Result<(), SerializeError> {
+    if minor_version > 9 {
+        return Err(SerializeError::Version);
+    }
+    out.extend_from_slice(b"HTTP/1.");
+    out.push(b'0' + minor_version);
+    Ok(())
+}
+
+// Writes each header line followed by the empty line ending the head.
+fn encode_headers<I, N, V>(out: &mut Vec<u8>, headers: I) -> Result<(), SerializeError>
+    where I: IntoIterator<Item = (N, V)>,
+          N: AsRef<[u8]>,
+          V: AsRef<[u8]>
+{
+    for (name, value) in headers {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        if name.is_empty() || !name.iter().all(|&b| is_token_char(b)) {
+            return Err(SerializeError::HeaderName);
+        }
+        if !is_field_text(value) {
+            return Err(SerializeError::HeaderValue);
+        }
+        out.extend_from_slice(name);
+        out.extend_from_slice(b": ");
+        out.extend_from_slice(value);
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(b"\r\n");
+    Ok(())
+}
+
+// Whether `text` can be sent as a header value or reason phrase. The parser
+// skips whitespace after the colon, and other implementations also strip it
+// at the end, so neither end may have any.
+fn is_field_text(text: &[u8]) -> bool {
+    let padded = match (text.first(), text.last()) {
+        (Some(&first), Some(&last)) => is_whitespace(first) || is_whitespace(last),
+        _ => false,
+    };
+    !padded && text.iter().all(|&b| b == b'\t' || !is_ctl(b))
+}
+
+fn is_whitespace(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn is_ctl(b: u8) -> bool {
+    b < 0x20 || b == 0x7f
+}
@@ -0,0 +1,143 @@
+//! Checks for framing ambiguities used in request smuggling.
+//!
+//! Intermediaries and backends can disagree on where a message ends when its
+//! Content-Length and Transfer-Encoding headers are contradictory or
+//! disguised. A proxy should reject messages with any of these violations
+//! instead of forwarding them.
+
+use std::cmp;
+use std::error;
+use std::fmt;
+
+use super::{is_token_char, trim_start, Headers, Request, Response};
+use framing::{is_chunked, list_elements, parse_decimal};
+
+/// A framing ambiguity found in a message head.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// Both Transfer-Encoding and Content-Length are present.
+    ContentLengthWithTransferEncoding,
+    /// Content-Length is given more than once, as repeated headers or as a
+    /// list, even if the values agree.
+    DuplicateContentLength,
+    /// A Content-Length value is not a plain decimal number.
+    InvalidContentLength,
+    /// A request's final transfer coding is not chunked.
+    ChunkedNotFinal,
+    /// The chunked transfer coding is applied more than once.
+    ChunkedRepeated,
+    /// An HTTP/1.0 message has a Transfer-Encoding header, which HTTP/1.0
+    /// recipients may ignore.
+    TransferEncodingInHttp10,
+    /// A Content-Length or Transfer-Encoding value is folded across lines.
+    FoldedFramingHeader,
+    /// A header line starts with whitespace followed by what looks like a
+    /// header name. picohttpparser treats it as a continuation of the
+    /// previous value, while other parsers may see a separate header.
+    WhitespaceBeforeHeaderName,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Violation::ContentLengthWithTransferEncoding => {
+                "both Content-Length and Transfer-Encoding are present"
+            }
+            Violation::DuplicateContentLength => "duplicate Content-Length",
+            Violation::InvalidContentLength => "invalid Content-Length",
+            Violation::ChunkedNotFinal => "chunked is not the final transfer coding",
+            Violation::ChunkedRepeated => "chunked is applied more than once",
+            Violation::TransferEncodingInHttp10 => "Transfer-Encoding in an HTTP/1.0 message",
+            Violation::FoldedFramingHeader => "folded Content-Length or Transfer-Encoding",
+            Violation::WhitespaceBeforeHeaderName => "whitespace before header name",
+        })
+    }
+}
+
+impl error::Error for Violation {}
+
+/// Returns the framing ambiguities in `req`, each reported once. An empty
+/// result means the request is safe to forward.
+pub fn check_request(req: &Request) -> Vec<Violation> {
+    check(&req.headers(), req.minor_version(), true)
+}
+
+/// Returns the framing ambiguities in `res`. Unlike requests, responses may
+/// end with a transfer coding other than chunked, in which case the body is
+/// delimited by closing the connection.
+pub fn check_response(res: &Response) -> Vec<Violation> {
+    check(&res.headers(), res.minor_version(), false)
+}
+
+fn check(headers: &Headers, minor_version: u8, is_request: bool) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut report = |violation| {
+        if !violations.contains(&violation) {
+            violations.push(violation);
+        }
+    };
+
+    let mut content_lengths = 0;
+    let mut transfer_encoding = false;
+    let mut codings = Vec::new();
+
+    for field in headers.fields() {
+        let is_content_length = field.name().eq_ignore_ascii_case(b"content-length");
+        let is_transfer_encoding = field.name().eq_ignore_ascii_case(b"transfer-encoding");
+
+        if field.is_folded() {
+            if is_content_length || is_transfer_encoding {
+                report(Violation::FoldedFramingHeader);
+            }
+            if field.segments().skip(1).any(looks_like_header_line) {
+                report(Violation::WhitespaceBeforeHeaderName);
+            }
+        }
+
+        let value = field.value();
+        if is_content_length {
+            let mut elements = 0;
+            for element in list_elements(&value) {
+                elements += 1;
+                if parse_decimal(element).is_none() {
+                    report(Violation::InvalidContentLength);
+                }
+            }
+            if elements == 0 {
+                report(Violation::InvalidContentLength);
+            }
+            content_lengths += cmp::max(elements, 1);
+        } else if is_transfer_encoding {
+            transfer_encoding = true;
+            codings.extend(list_elements(&value).map(is_chunked));
+        }
+    }
+
+    if content_lengths > 1 {
+        report(Violation::DuplicateContentLength);
+    }
+    if transfer_encoding {
+        if content_lengths > 0 {
+            report(Violation::ContentLengthWithTransferEncoding);
+        }
+        if minor_version == 0 {
+            report(Violation::TransferEncodingInHttp10);
+        }
+        if codings.iter().filter(|&&chunked| chunked).count() > 1 {
+            report(Violation::ChunkedRepeated);
+        }
+        if is_request && codings.last() != Some(&true) {
+            report(Violation::ChunkedNotFinal);
+        }
+    }
+    violations
+}
+
+// Returns `true` for a continuation line of the form `name: value`.
+fn looks_like_header_line(segment: &[u8]) -> bool {
+    let line = trim_start(segment);
+    match line.iter().position(|&b| b == b':') {
+        Some(colon) => colon > 0 && line[..colon].iter().all(|&b| is_token_char(b)),
+        None => false,
+    }
+}
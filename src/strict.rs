@@ -0,0 +1,113 @@
+use super::{is_token_char, trim_end, ParseError};
+
+// What the first line of a head is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StartLine {
+    Request,
+    Status,
+    None,
+}
+
+// Checks the complete lines of the head at the start of `buf` against the
+// RFC 9112 rules that the C parser does not enforce or only reports as
+// malformed input, returning the first violation.
+//
+// Incomplete lines are left alone, as is anything else the C parser rejects
+// on its own.
+//
+// Checking starts at `checked`, a value returned by an earlier call on a
+// prefix of `buf`, and the return value is where the next call can resume:
+// the end of the last header line checked, or 0 while the start line is not
+// complete.
+pub(crate) fn check(buf: &[u8],
+                    start_line: StartLine,
+                    checked: usize)
+                    -> Result<usize, ParseError> {
+    let mut lines = Lines { rest: &buf[checked..] };
+
+    if checked == 0 {
+        if start_line == StartLine::Request {
+            // A request may be preceded by an empty line, see RFC 9112
+            // section 2.2.
+            if buf.first() == Some(&b'\r') || buf.first() == Some(&b'\n') {
+                match lines.next() {
+                    Some(line) => line?,
+                    None => return Ok(0),
+                };
+            }
+            match lines.next() {
+                Some(line) => check_request_line(line?)?,
+                None => return Ok(0),
+            }
+        } else if start_line == StartLine::Status {
+            match lines.next() {
+                Some(line) => line?,
+                None => return Ok(0),
+            };
+        }
+    }
+
+    let mut checked = buf.len() - lines.rest.len();
+    for line in lines {
+        let line = line?;
+        if line.is_empty() {
+            break;
+        }
+        check_header_line(line)?;
+        checked += line.len() + 2;
+    }
+    Ok(checked)
+}
+
+fn check_request_line(line: &[u8]) -> Result<(), ParseError> {
+    let mut parts = line.splitn(3, |&b| b == b' ');
+    let method = parts.next().unwrap_or(b"");
+    if method.is_empty() || !method.iter().all(|&b| is_token_char(b)) {
+        return Err(ParseError::InvalidMethod);
+    }
+    let target = parts.next().unwrap_or(b"");
+    if target.is_empty() || !target.iter().all(|&b| b > b' ' && b < 0x7f) {
+        return Err(ParseError::InvalidTarget);
+    }
+    Ok(())
+}
+
+fn check_header_line(line: &[u8]) -> Result<(), ParseError> {
+    if line[0] == b' ' || line[0] == b'\t' {
+        return Err(ParseError::ObsFold);
+    }
+    let name = match line.iter().position(|&b| b == b':') {
+        Some(colon) => {
+            let name = &line[..colon];
+            if trim_end(name).len() != name.len() {
+                return Err(ParseError::WhitespaceBeforeColon);
+            }
+            name
+        }
+        None => line,
+    };
+    if !name.iter().all(|&b| is_token_char(b)) {
+        return Err(ParseError::InvalidHeaderName);
+    }
+    Ok(())
+}
+
+// Iterator over the complete lines at the start of a buffer, without their
+// CRLF. A line ending in a bare LF is an error.
+struct Lines<'buf> {
+    rest: &'buf [u8],
+}
+
+impl<'buf> Iterator for Lines<'buf> {
+    type Item = Result<&'buf [u8], ParseError>;
+
+    fn next(&mut self) -> Option<Result<&'buf [u8], ParseError>> {
+        let end = self.rest.iter().position(|&b| b == b'\n')?;
+        let line = &self.rest[..end];
+        self.rest = &self.rest[end + 1..];
+        Some(match line.split_last() {
+            Some((&b'\r', line)) => Ok(line),
+            _ => Err(ParseError::BareLf),
+        })
+    }
+}
@@ -0,0 +1,213 @@
+use super::{Method, ParseError};
+
+/// A request-target classified into the four forms of RFC 9112 section 3.2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestTarget<'buf> {
+    /// `/path?query`, used for requests to an origin server.
+    Origin {
+        path: &'buf [u8],
+        query: Option<&'buf [u8]>,
+    },
+    /// `scheme://authority/path?query`, used for requests to a proxy. The
+    /// path is empty if the target has none.
+    Absolute {
+        scheme: &'buf [u8],
+        authority: Authority<'buf>,
+        path: &'buf [u8],
+        query: Option<&'buf [u8]>,
+    },
+    /// `host:port`, only used with CONNECT.
+    Authority(Authority<'buf>),
+    /// `*`, only used with OPTIONS.
+    Asterisk,
+}
+
+impl<'buf> RequestTarget<'buf> {
+    /// Classifies `target`, the request-target of a request with the method
+    /// `method`.
+    ///
+    /// CONNECT requests must use the authority-form with a port, and only
+    /// OPTIONS requests may use the asterisk-form. Absolute-form targets
+    /// must have an authority with a host (`scheme://host...`). Anything
+    /// else, including a fragment, is `ParseError::InvalidTarget`.
+    pub fn parse(target: &'buf [u8], method: Method) -> Result<RequestTarget<'buf>, ParseError> {
+        if target.contains(&b'#') {
+            return Err(ParseError::InvalidTarget);
+        }
+        if method == Method::Connect {
+            let authority = Authority::parse(target)?;
+            if authority.userinfo.is_some() || authority.host.is_empty() ||
+               authority.port.is_none() {
+                return Err(ParseError::InvalidTarget);
+            }
+            return Ok(RequestTarget::Authority(authority));
+        }
+        if target == b"*" {
+            return if method == Method::Options {
+                Ok(RequestTarget::Asterisk)
+            } else {
+                Err(ParseError::InvalidTarget)
+            };
+        }
+        if target.first() == Some(&b'/') {
+            let (path, query) = split_query(target);
+            return Ok(RequestTarget::Origin { path, query });
+        }
+
+        let scheme_len = match target.iter().position(|&b| b == b':') {
+            Some(len) if is_scheme(&target[..len]) => len,
+            _ => return Err(ParseError::InvalidTarget),
+        };
+        let scheme = &target[..scheme_len];
+        let rest = &target[scheme_len + 1..];
+        if !rest.starts_with(b"//") {
+            return Err(ParseError::InvalidTarget);
+        }
+        let rest = &rest[2..];
+        let authority_len = rest.iter().position(|&b| b == b'/' || b == b'?').unwrap_or(rest.len());
+        let authority = Authority::parse(&rest[..authority_len])?;
+        if authority.host.is_empty() {
+            return Err(ParseError::InvalidTarget);
+        }
+        let (path, query) = split_query(&rest[authority_len..]);
+        Ok(RequestTarget::Absolute {
+            scheme,
+            authority,
+            path,
+            query,
+        })
+    }
+
+    /// The path of an origin-form or absolute-form target, and an empty
+    /// slice otherwise.
+    pub fn path(&self) -> &'buf [u8] {
+        match *self {
+            RequestTarget::Origin { path, .. } | RequestTarget::Absolute { path, .. } => path,
+            RequestTarget::Authority(_) | RequestTarget::Asterisk => b"",
+        }
+    }
+
+    /// The query, without the `?`, of an origin-form or absolute-form
+    /// target.
+    pub fn query(&self) -> Option<&'buf [u8]> {
+        match *self {
+            RequestTarget::Origin { query, .. } | RequestTarget::Absolute { query, .. } => query,
+            RequestTarget::Authority(_) | RequestTarget::Asterisk => None,
+        }
+    }
+
+    /// The authority of an absolute-form or authority-form target.
+    pub fn authority(&self) -> Option<Authority<'buf>> {
+        match *self {
+            RequestTarget::Absolute { authority, .. } | RequestTarget::Authority(authority) => {
+                Some(authority)
+            }
+            RequestTarget::Origin { .. } | RequestTarget::Asterisk => None,
+        }
+    }
+}
+
+/// The `[userinfo@]host[:port]` part of a URI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Authority<'buf> {
+    raw: &'buf [u8],
+    userinfo: Option<&'buf [u8]>,
+    host: &'buf [u8],
+    port: Option<u16>,
+}
+
+impl<'buf> Authority<'buf> {
+    fn parse(raw: &'buf [u8]) -> Result<Authority<'buf>, ParseError> {
+        let (userinfo, host_port) = match raw.iter().rposition(|&b| b == b'@') {
+            Some(at) => (Some(&raw[..at]), &raw[at + 1..]),
+            None => (None, raw),
+        };
+
+        let host_len = if host_port.first() == Some(&b'[') {
+            match host_port.iter().position(|&b| b == b']') {
+                Some(end) => end + 1,
+                None => return Err(ParseError::InvalidTarget),
+            }
+        } else {
+            host_port.iter().position(|&b| b == b':').unwrap_or(host_port.len())
+        };
+        let (host, port) = host_port.split_at(host_len);
+        if !is_host(host) {
+            return Err(ParseError::InvalidTarget);
+        }
+
+        let port = match port.split_first() {
+            None | Some((&b':', b"")) => None,
+            Some((&b':', digits)) => Some(parse_port(digits)?),
+            Some(_) => return Err(ParseError::InvalidTarget),
+        };
+
+        Ok(Authority {
+            raw,
+            userinfo,
+            host,
+            port,
+        })
+    }
+
+    /// The authority as sent.
+    pub fn as_bytes(&self) -> &'buf [u8] {
+        self.raw
+    }
+
+    pub fn userinfo(&self) -> Option<&'buf [u8]> {
+        self.userinfo
+    }
+
+    /// The host, including the brackets of an IP literal.
+    pub fn host(&self) -> &'buf [u8] {
+        self.host
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+}
+
+fn split_query(target: &[u8]) -> (&[u8], Option<&[u8]>) {
+    match target.iter().position(|&b| b == b'?') {
+        Some(i) => (&target[..i], Some(&target[i + 1..])),
+        None => (target, None),
+    }
+}
+
+// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+fn is_scheme(scheme: &[u8]) -> bool {
+    match scheme.split_first() {
+        Some((first, rest)) => {
+            first.is_ascii_alphabetic() &&
+            rest.iter().all(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.')
+        }
+        None => false,
+    }
+}
+
+// Accepts a reg-name, or an IP literal in brackets without checking its
+// address syntax beyond the allowed characters.
+fn is_host(host: &[u8]) -> bool {
+    let is_unreserved_or_sub_delim = |b: u8| {
+        b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=%".contains(&b)
+    };
+    if host.first() == Some(&b'[') {
+        host.len() > 2 &&
+        host[1..host.len() - 1].iter().all(|&b| is_unreserved_or_sub_delim(b) || b == b':')
+    } else {
+        host.iter().all(|&b| is_unreserved_or_sub_delim(b))
+    }
+}
+
+fn parse_port(digits: &[u8]) -> Result<u16, ParseError> {
+    if digits.is_empty() || digits.len() > 5 || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(ParseError::InvalidTarget);
+    }
+    let port = digits.iter().fold(0u32, |port, &b| port * 10 + (b - b'0') as u32);
+    if port > u16::MAX as u32 {
+        return Err(ParseError::InvalidTarget);
+    }
+    Ok(port as u16)
+}
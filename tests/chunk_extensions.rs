@@ -0,0 +1,114 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+use std::borrow::Cow;
+
+fn extensions(chunk: &Chunk) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+    chunk.extensions()
+        .map(|e| (e.name().to_vec(), e.value().map(Cow::into_owned)))
+        .collect()
+}
+
+#[test]
+fn chunks_with_extensions() {
+    let buf = b"5;sig=abc123;final\r\nhello\r\n\
+                6 ; note = \"a \\\"b\\\"\" \r\n world\r\n\
+                0;count=2\r\ngrpc-status: 0\r\n\r\n";
+    let mut chunks = Chunks::new(buf);
+
+    let chunk = chunks.next().unwrap().unwrap();
+    assert_eq!(5, chunk.size());
+    assert_eq!(b"hello", chunk.data());
+    assert_eq!(vec![(b"sig".to_vec(), Some(b"abc123".to_vec())), (b"final".to_vec(), None)],
+               extensions(&chunk));
+
+    let chunk = chunks.next().unwrap().unwrap();
+    assert_eq!(b" world", chunk.data());
+    let extension = chunk.extensions().next().unwrap();
+    assert_eq!(b"note", extension.name());
+    assert_eq!(Some(&b"\"a \\\"b\\\"\""[..]), extension.raw_value());
+    assert_eq!(Some(Cow::Owned(b"a \"b\"".to_vec())), extension.value());
+
+    let chunk = chunks.next().unwrap().unwrap();
+    assert!(chunk.is_last());
+    assert_eq!(0, chunk.size());
+    assert_eq!(vec![(b"count".to_vec(), Some(b"2".to_vec()))], extensions(&chunk));
+
+    assert!(chunks.next().is_none());
+    assert!(chunks.is_complete());
+    assert_eq!(b"grpc-status: 0\r\n\r\n", chunks.remainder());
+}
+
+#[test]
+fn quoted_value_without_escapes_is_borrowed() {
+    let mut chunks = Chunks::new(b"1;a=\"x y\"\r\nz\r\n");
+    let chunk = chunks.next().unwrap().unwrap();
+    match chunk.extensions().next().unwrap().value() {
+        Some(Cow::Borrowed(value)) => assert_eq!(b"x y", value),
+        value => panic!("unexpected {:?}", value),
+    }
+}
+
+#[test]
+fn stops_before_incomplete_chunk() {
+    let buf = b"3;a=b\r\nabc\r\n4;c=d\r\nde";
+    let mut chunks = Chunks::new(buf);
+    assert_eq!(b"abc", chunks.next().unwrap().unwrap().data());
+    assert!(chunks.next().is_none());
+    assert!(!chunks.is_complete());
+    assert_eq!(12, chunks.offset());
+    assert_eq!(b"4;c=d\r\nde", chunks.remainder());
+
+    for partial in &[&b"4"[..], b"4;c=", b"4\r\nabcd", b"4\r\nabcd\r"] {
+        let mut chunks = Chunks::new(partial);
+        assert!(chunks.next().is_none());
+        assert_eq!(0, chunks.offset());
+    }
+}
+
+#[test]
+fn extension_length_cap() {
+    let mut chunks = Chunks::with_max_extension_len(b"1;abc=def\r\nx\r\n", 8);
+    assert_eq!(1, chunks.next().unwrap().unwrap().extensions().count());
+
+    let mut chunks = Chunks::with_max_extension_len(b"1;abc=defg\r\nx\r\n", 8);
+    assert_eq!(Some(Err(ParseError::ChunkExtensionTooLong)),
+               chunks.next().map(|c| c.map(|c| c.size())));
+    assert!(chunks.next().is_none());
+
+    // An unterminated line is rejected as soon as it is too long.
+    let long = [&b"1;"[..], &[b'a'; 2000][..]].concat();
+    let mut chunks = Chunks::new(&long);
+    assert_eq!(Some(Err(ParseError::ChunkExtensionTooLong)),
+               chunks.next().map(|c| c.map(|c| c.size())));
+}
+
+#[test]
+fn malformed() {
+    let bad: &[&[u8]] = &[b"z\r\n",
+                          b"1x\r\na\r\n",
+                          b"1;\r\na\r\n",
+                          b"1;a=\r\na\r\n",
+                          b"1;a=\"b\r\na\r\n",
+                          b"1;a=b c\r\na\r\n",
+                          b"1;a=\"\x01\"\r\na\r\n",
+                          b"1\r\nab\r\n",
+                          b"11111111111111111\r\n"];
+    for buf in bad {
+        let mut chunks = Chunks::new(buf);
+        assert_eq!(Some(Err(ParseError::ChunkedEncoding)),
+                   chunks.next().map(|c| c.map(|c| c.size())),
+                   "{:?}",
+                   String::from_utf8_lossy(buf));
+    }
+}
+
+#[test]
+fn agrees_with_decoder() {
+    let buf = b"6;x=1\r\nhello \r\n5\nworld\r\r\n0\r\n\r\n";
+    let payload: Vec<u8> = Chunks::new(buf).flat_map(|c| c.unwrap().data().to_vec()).collect();
+
+    let mut decoded = Vec::new();
+    ChunkedDecoder::new(true).decode_into(buf, &mut decoded).unwrap();
+    assert_eq!(decoded, payload);
+}
@@ -0,0 +1,302 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn decode_at_once(consume_trailer: bool,
+                  encoded: &[u8])
+                  -> Result<(Vec<u8>, ChunkedStatus), ParseError> {
+    let mut decoder = ChunkedDecoder::new(consume_trailer);
+    let mut buf = encoded.to_vec();
+    let status = decoder.decode(&mut buf)?;
+    let decoded = match status {
+        ChunkedStatus::Complete { decoded, .. } | ChunkedStatus::Partial { decoded } => decoded,
+    };
+    Ok((buf[..decoded].to_vec(), status))
+}
+
+fn decode_per_byte(consume_trailer: bool,
+                   encoded: &[u8])
+                   -> Result<(Vec<u8>, ChunkedStatus), ParseError> {
+    let mut decoder = ChunkedDecoder::new(consume_trailer);
+    let mut out = Vec::new();
+    for (i, &byte) in encoded.iter().enumerate() {
+        let mut buf = [byte];
+        match decoder.decode(&mut buf)? {
+            ChunkedStatus::Partial { decoded } => out.extend_from_slice(&buf[..decoded]),
+            ChunkedStatus::Complete { decoded, .. } => {
+                out.extend_from_slice(&buf[..decoded]);
+                let remaining = encoded.len() - i - 1;
+                return Ok((out, ChunkedStatus::Complete { decoded: 0, remaining }));
+            }
+        }
+    }
+    Ok((out, ChunkedStatus::Partial { decoded: 0 }))
+}
+
+fn decode_into_per_byte(consume_trailer: bool,
+                        encoded: &[u8])
+                        -> Result<(Vec<u8>, ChunkedStatus), ParseError> {
+    let mut decoder = ChunkedDecoder::new(consume_trailer);
+    let mut out = Vec::new();
+    for (i, byte) in encoded.iter().enumerate() {
+        if let ChunkedStatus::Complete { .. } = decoder.decode_into(&[*byte], &mut out)? {
+            let remaining = encoded.len() - i - 1;
+            return Ok((out, ChunkedStatus::Complete { decoded: 0, remaining }));
+        }
+    }
+    Ok((out, ChunkedStatus::Partial { decoded: 0 }))
+}
+
+fn slices_at_once(consume_trailer: bool,
+                  encoded: &[u8])
+                  -> Result<(Vec<u8>, Option<usize>), ParseError> {
+    let mut decoder = ChunkedDecoder::new(consume_trailer);
+    let mut slices = decoder.slices(encoded);
+    let mut out = Vec::new();
+    for slice in &mut slices {
+        let slice = slice?;
+        assert!(encoded.as_ptr() <= slice.as_ptr() &&
+                slice.as_ptr() as usize + slice.len() <= encoded.as_ptr() as usize + encoded.len());
+        out.extend_from_slice(slice);
+    }
+    let remaining = if slices.is_complete() {
+        Some(slices.remainder().len())
+    } else {
+        None
+    };
+    Ok((out, remaining))
+}
+
+fn slices_per_byte(consume_trailer: bool,
+                   encoded: &[u8])
+                   -> Result<(Vec<u8>, Option<usize>), ParseError> {
+    let mut decoder = ChunkedDecoder::new(consume_trailer);
+    let mut out = Vec::new();
+    for (i, byte) in encoded.iter().enumerate() {
+        let mut slices = decoder.slices(std::slice::from_ref(byte));
+        for slice in &mut slices {
+            out.extend_from_slice(slice?);
+        }
+        if slices.is_complete() {
+            return Ok((out, Some(encoded.len() - i - 1)));
+        }
+    }
+    Ok((out, None))
+}
+
+fn check(consume_trailer: bool, encoded: &[u8], expected: &[u8], remaining: Option<usize>) {
+    let (decoded, status) = decode_at_once(consume_trailer, encoded).unwrap();
+    assert_eq!(expected, &decoded[..]);
+    match remaining {
+        Some(remaining) => {
+            assert_eq!(ChunkedStatus::Complete { decoded: expected.len(), remaining },
+                       status)
+        }
+        None => assert_eq!(ChunkedStatus::Partial { decoded: expected.len() }, status),
+    }
+
+    let mut decoded = b"prefix".to_vec();
+    let status = ChunkedDecoder::new(consume_trailer).decode_into(encoded, &mut decoded).unwrap();
+    assert_eq!(b"prefix", &decoded[..6]);
+    assert_eq!(expected, &decoded[6..]);
+    match remaining {
+        Some(remaining) => {
+            assert_eq!(ChunkedStatus::Complete { decoded: expected.len(), remaining },
+                       status)
+        }
+        None => assert_eq!(ChunkedStatus::Partial { decoded: expected.len() }, status),
+    }
+
+    for walk in &[slices_at_once, slices_per_byte] {
+        assert_eq!((expected.to_vec(), remaining), walk(consume_trailer, encoded).unwrap());
+    }
+
+    for decode in &[decode_per_byte, decode_into_per_byte] {
+        let (decoded, status) = decode(consume_trailer, encoded).unwrap();
+        assert_eq!(expected, &decoded[..]);
+        match remaining {
+            Some(remaining) => {
+                assert_eq!(ChunkedStatus::Complete { decoded: 0, remaining }, status)
+            }
+            None => assert_eq!(ChunkedStatus::Partial { decoded: 0 }, status),
+        }
+    }
+}
+
+#[test]
+fn chunked() {
+    check(false, b"b\r\nhello world\r\n0\r\n", b"hello world", Some(0));
+    check(false, b"6\r\nhello \r\n5\r\nworld\r\n0\r\n", b"hello world", Some(0));
+    check(false, b"6;comment=hi\r\nhello \r\n5\r\nworld\r\n0\r\n", b"hello world", Some(0));
+    check(false,
+          b"6\r\nhello \r\n5\r\nworld\r\n0\r\na: b\r\nc: d\r\n\r\n",
+          b"hello world",
+          Some(b"a: b\r\nc: d\r\n\r\n".len()));
+}
+
+#[test]
+fn consume_trailer() {
+    check(true, b"b\r\nhello world\r\n0\r\n", b"hello world", None);
+    check(true, b"6\r\nhello \r\n5\r\nworld\r\n0\r\n", b"hello world", None);
+    check(true, b"6;comment=hi\r\nhello \r\n5\r\nworld\r\n0\r\n", b"hello world", None);
+    check(true, b"b\r\nhello world\r\n0\r\n\r\n", b"hello world", Some(0));
+    check(true, b"b\nhello world\n0\n\n", b"hello world", Some(0));
+    check(true,
+          b"6\r\nhello \r\n5\r\nworld\r\n0\r\na: b\r\nc: d\r\n\r\n",
+          b"hello world",
+          Some(0));
+}
+
+#[test]
+fn leftover_follows_payload() {
+    let mut buf = b"5\r\nhello\r\n0\r\n\r\nGET / HTTP/1.1\r\n".to_vec();
+    let mut decoder = ChunkedDecoder::new(true);
+    match decoder.decode(&mut buf).unwrap() {
+        ChunkedStatus::Complete { decoded, remaining } => {
+            assert_eq!(b"hello", &buf[..decoded]);
+            assert_eq!(b"GET / HTTP/1.1\r\n", &buf[decoded..decoded + remaining]);
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+}
+
+#[test]
+fn failure() {
+    assert_eq!(ParseError::ChunkedEncoding,
+               decode_at_once(false, b"z\r\nabcdefg").unwrap_err());
+    assert_eq!(ParseError::ChunkedEncoding,
+               decode_per_byte(false, b"z\r\nabcdefg").unwrap_err());
+    assert_eq!(ParseError::ChunkedEncoding,
+               decode_into_per_byte(false, b"z\r\nabcdefg").unwrap_err());
+    assert_eq!(ParseError::ChunkedEncoding,
+               slices_at_once(false, b"z\r\nabcdefg").unwrap_err());
+    assert_eq!(ParseError::ChunkedEncoding,
+               slices_per_byte(false, b"3\r\nabcX").unwrap_err());
+}
+
+#[test]
+fn slices_split_chunks() {
+    let mut decoder = ChunkedDecoder::new(true);
+    let first = b"6\r\nhel";
+    let slices: Vec<_> = decoder.slices(first).map(Result::unwrap).collect();
+    assert_eq!(vec![&b"hel"[..]], slices);
+    assert!(decoder.is_in_data());
+
+    let second = b"lo \r\n5\r\nworld\r\n0\r\n\r\nnext";
+    let mut slices = decoder.slices(second);
+    assert_eq!(Some(Ok(&b"lo "[..])), slices.next());
+    assert_eq!(Some(Ok(&b"world"[..])), slices.next());
+    assert_eq!(None, slices.next());
+    assert!(slices.is_complete());
+    assert_eq!(b"next", slices.remainder());
+    assert_eq!(second.len() - 4, slices.offset());
+}
+
+#[test]
+fn slices_mix_with_decode() {
+    let mut decoder = ChunkedDecoder::new(false);
+    assert_eq!(Some(Ok(&b"ab"[..])), decoder.slices(b"5\r\nab").next());
+    let mut buf = b"cde\r\n0\r\n".to_vec();
+    assert_eq!(ChunkedStatus::Complete { decoded: 3, remaining: 0 },
+               decoder.decode(&mut buf).unwrap());
+    assert_eq!(b"cde", &buf[..3]);
+}
+
+#[test]
+fn decode_into_leaves_input_alone() {
+    let input: &[u8] = b"5\r\nhello\r\n0\r\n\r\nGET / HTTP/1.1\r\n";
+    let mut out = Vec::new();
+    let mut decoder = ChunkedDecoder::new(true);
+    match decoder.decode_into(input, &mut out).unwrap() {
+        ChunkedStatus::Complete { decoded: 5, remaining } => {
+            assert_eq!(b"GET / HTTP/1.1\r\n", &input[input.len() - remaining..]);
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+    assert_eq!(b"hello", &out[..]);
+
+    let mut out = b"kept".to_vec();
+    let mut decoder = ChunkedDecoder::new(false);
+    assert!(decoder.decode_into(b"3\r\nabcX", &mut out).is_err());
+    assert_eq!(b"kept", &out[..]);
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn chunk_size_overflow() {
+    let (_, status) = decode_at_once(false, b"6\r\nhello \r\nffffffffffffffff\r\nabcdefg")
+        .unwrap();
+    assert_eq!(ChunkedStatus::Partial { decoded: 13 }, status);
+    assert_eq!((b"hello abcdefg".to_vec(), None),
+               slices_at_once(false, b"6\r\nhello \r\nffffffffffffffff\r\nabcdefg").unwrap());
+    assert_eq!(ParseError::ChunkedEncoding,
+               slices_at_once(false, b"6\r\nhello \r\nfffffffffffffffff\r\nabcdefg")
+                   .unwrap_err());
+    assert_eq!(ParseError::ChunkedEncoding,
+               decode_at_once(false, b"6\r\nhello \r\nfffffffffffffffff\r\nabcdefg")
+                   .unwrap_err());
+}
+
+#[test]
+fn is_in_data() {
+    let mut decoder = ChunkedDecoder::new(false);
+    assert!(!decoder.is_in_data());
+    let mut buf = b"5\r\nhel".to_vec();
+    assert_eq!(ChunkedStatus::Partial { decoded: 3 }, decoder.decode(&mut buf).unwrap());
+    assert!(decoder.is_in_data());
+    let mut buf = b"lo\r\n".to_vec();
+    assert_eq!(ChunkedStatus::Partial { decoded: 2 }, decoder.decode(&mut buf).unwrap());
+    assert!(!decoder.is_in_data());
+}
+
+#[test]
+fn trailers() {
+    let mut buf = b"4\r\ndata\r\n0\r\ngrpc-status: 0\r\nContent-MD5: abc\r\n\r\nGET / HTTP/1.1\r\n"
+        .to_vec();
+    let mut decoder = ChunkedDecoder::new(false);
+    let (decoded, remaining) = match decoder.decode(&mut buf).unwrap() {
+        ChunkedStatus::Complete { decoded, remaining } => (decoded, remaining),
+        status => panic!("unexpected {:?}", status),
+    };
+    assert_eq!(b"data", &buf[..decoded]);
+
+    let tail = &buf[decoded..decoded + remaining];
+    let mut headers = [phr_header::default(); 4];
+    match ChunkedDecoder::parse_trailers(tail, &mut headers).unwrap() {
+        Status::Complete(consumed, trailers) => {
+            assert_eq!(b"GET / HTTP/1.1\r\n", &tail[consumed..]);
+            assert_eq!(2, trailers.len());
+            assert_eq!(Some(&b"0"[..]), trailers.get(b"Grpc-Status"));
+            assert_eq!(Some(&b"abc"[..]), trailers.get(b"content-md5"));
+        }
+        Status::Partial => panic!("partial"),
+    }
+}
+
+#[test]
+fn no_trailers() {
+    let mut buf = b"0\r\n\r\n".to_vec();
+    let remaining = match ChunkedDecoder::new(false).decode(&mut buf).unwrap() {
+        ChunkedStatus::Complete { decoded: 0, remaining } => remaining,
+        status => panic!("unexpected {:?}", status),
+    };
+    let mut headers = [phr_header::default(); 4];
+    let status = ChunkedDecoder::parse_trailers(&buf[..remaining], &mut headers).unwrap();
+    assert_eq!(Status::Complete(2, 0), status.map(|trailers| trailers.len()));
+}
+
+#[test]
+fn partial_trailers() {
+    let mut headers = [phr_header::default(); 4];
+    let status = ChunkedDecoder::parse_trailers(b"grpc-status: 0\r\n", &mut headers).unwrap();
+    assert!(status.is_partial());
+
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(Err(ParseError::TooManyHeaders { needed: 2 }),
+               ChunkedDecoder::parse_trailers(b"a: 1\r\nb: 2\r\n\r\n", &mut headers).map(|_| ()));
+
+    let mut storage = HeaderBuffer::new();
+    let status = ChunkedDecoder::parse_trailers_growing(b"a: 1\r\nb: 2\r\n\r\n", &mut storage)
+        .unwrap();
+    assert_eq!(Status::Complete(14, 2), status.map(|trailers| trailers.len()));
+}
@@ -0,0 +1,67 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+use std::io::Write;
+
+const NO_FIELDS: Option<(&str, &str)> = None;
+
+fn decode(encoded: &[u8]) -> Vec<u8> {
+    let mut buf = encoded.to_vec();
+    match ChunkedDecoder::new(true).decode(&mut buf).unwrap() {
+        ChunkedStatus::Complete { decoded, remaining: 0 } => buf[..decoded].to_vec(),
+        status => panic!("unexpected {:?}", status),
+    }
+}
+
+#[test]
+fn write_adapter() {
+    let mut encoder = ChunkedEncoder::new(Vec::new());
+    encoder.write_all(b"hello ").unwrap();
+    encoder.write_all(b"world").unwrap();
+    encoder.write_all(b"").unwrap();
+    let encoded = encoder.finish(NO_FIELDS).unwrap();
+
+    assert_eq!(&b"6\r\nhello \r\n5\r\nworld\r\n0\r\n\r\n"[..], &encoded[..]);
+    assert_eq!(b"hello world", &decode(&encoded)[..]);
+}
+
+#[test]
+fn large_chunk() {
+    let data = vec![b'x'; 0x1234];
+    let mut encoder = ChunkedEncoder::new(Vec::new());
+    encoder.write_chunk(&data, NO_FIELDS).unwrap();
+    let encoded = encoder.finish(NO_FIELDS).unwrap();
+
+    assert!(encoded.starts_with(b"1234\r\n"));
+    assert_eq!(data, decode(&encoded));
+}
+
+#[test]
+fn extensions() {
+    let mut encoder = ChunkedEncoder::new(Vec::new());
+    encoder.write_chunk(b"abc", vec![("sig", "0a1b"), ("last", "")]).unwrap();
+    encoder.write_chunk(b"de", vec![("note", "a \"quoted\" \\ value")]).unwrap();
+    assert_eq!(&b"3;sig=0a1b;last\r\nabc\r\n2;note=\"a \\\"quoted\\\" \\\\ value\"\r\nde\r\n"[..],
+               &encoder.get_ref()[..]);
+
+    let err = encoder.write_chunk(b"f", vec![("a b", "")]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+    let err = encoder.write_chunk(b"f", vec![("a", "\r\n")]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidInput, err.kind());
+
+    let encoded = encoder.finish(NO_FIELDS).unwrap();
+    assert_eq!(b"abcde", &decode(&encoded)[..]);
+}
+
+#[test]
+fn trailers() {
+    let mut encoder = ChunkedEncoder::new(Vec::new());
+    encoder.write_all(b"data").unwrap();
+    let encoded = encoder.finish(vec![("grpc-status", "0"), ("Checksum", "abc")]).unwrap();
+    assert_eq!(&b"4\r\ndata\r\n0\r\ngrpc-status: 0\r\nChecksum: abc\r\n\r\n"[..],
+               &encoded[..]);
+    assert_eq!(b"data", &decode(&encoded)[..]);
+
+    let encoder = ChunkedEncoder::new(Vec::new());
+    assert!(encoder.finish(vec![("X", "a\r\nInjected: 1")]).is_err());
+}
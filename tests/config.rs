@@ -0,0 +1,164 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+const REQUEST: &[u8] = b"GET /0123456789 HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n";
+
+fn request_error(config: &ParserConfig, buf: &[u8]) -> Option<ParseError> {
+    let mut headers = [phr_header::default(); 8];
+    config.parse_request(buf, &mut headers).err()
+}
+
+#[test]
+fn no_limits_by_default() {
+    let config = ParserConfig::new();
+    assert_eq!(None, request_error(&config, REQUEST));
+    assert_eq!(None, request_error(&config, &REQUEST[..20]));
+}
+
+#[test]
+fn head_len() {
+    let mut config = ParserConfig::new();
+    config.max_head_len(REQUEST.len());
+    assert_eq!(None, request_error(&config, REQUEST));
+
+    config.max_head_len(REQUEST.len() - 1);
+    assert_eq!(Some(ParseError::HeadTooLarge), request_error(&config, REQUEST));
+
+    config.max_head_len(10);
+    assert_eq!(None, request_error(&config, &REQUEST[..10]));
+    assert_eq!(Some(ParseError::HeadTooLarge), request_error(&config, &REQUEST[..11]));
+}
+
+#[test]
+fn target_len() {
+    let mut config = ParserConfig::new();
+    config.max_target_len(11);
+    assert_eq!(None, request_error(&config, REQUEST));
+
+    config.max_target_len(10);
+    assert_eq!(Some(ParseError::TargetTooLong), request_error(&config, REQUEST));
+    assert_eq!(None, request_error(&config, b"GET /012345678"));
+    assert_eq!(Some(ParseError::TargetTooLong),
+               request_error(&config, b"GET /0123456789"));
+    assert_eq!(Some(ParseError::TargetTooLong),
+               request_error(&config, b"\r\nGET /0123456789 HT"));
+}
+
+#[test]
+fn header_field_len() {
+    let mut config = ParserConfig::new();
+    config.max_header_name_len(6).max_header_value_len(11);
+    assert_eq!(None, request_error(&config, REQUEST));
+
+    config.max_header_name_len(5);
+    assert_eq!(Some(ParseError::HeaderNameTooLong), request_error(&config, REQUEST));
+
+    config.max_header_name_len(6).max_header_value_len(10);
+    assert_eq!(Some(ParseError::HeaderValueTooLong), request_error(&config, REQUEST));
+}
+
+#[test]
+fn header_count() {
+    let mut config = ParserConfig::new();
+    config.max_headers(2);
+    assert_eq!(None, request_error(&config, REQUEST));
+
+    config.max_headers(1);
+    assert_eq!(Some(ParseError::TooManyHeaders { needed: 2 }),
+               request_error(&config, REQUEST));
+
+    let buf: Vec<u8> = (0..50)
+        .flat_map(|i| format!("X-{}: {}\r\n", i, i).into_bytes())
+        .chain(b"\r\n".iter().cloned())
+        .collect();
+    let mut storage = HeaderBuffer::new();
+    config.max_headers(40);
+    assert_eq!(Err(ParseError::TooManyHeaders { needed: 50 }),
+               config.parse_headers_growing(&buf, &mut storage).map(|s| s.is_complete()));
+    config.max_headers(50);
+    match config.parse_headers_growing(&buf, &mut storage).unwrap() {
+        Status::Complete(_, headers) => assert_eq!(50, headers.len()),
+        Status::Partial => panic!("partial"),
+    }
+}
+
+#[test]
+fn responses_and_headers() {
+    let mut config = ParserConfig::new();
+    config.max_head_len(30).max_header_value_len(3);
+
+    let mut headers = [phr_header::default(); 4];
+    let status = config.parse_response(b"HTTP/1.1 200 OK\r\nA: 123\r\n\r\n", &mut headers);
+    assert!(status.unwrap().is_complete());
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(Err(ParseError::HeaderValueTooLong),
+               config.parse_response(b"HTTP/1.1 200 OK\r\nA: 1234\r\n\r\n", &mut headers)
+                   .map(|s| s.is_complete()));
+    let mut storage = HeaderBuffer::new();
+    assert_eq!(Err(ParseError::HeadTooLarge),
+               config.parse_response_growing(b"HTTP/1.1 200 OK\r\nA: 1\r\nB: 2\r\nC: 3\r\n",
+                                             &mut storage)
+                   .map(|s| s.is_complete()));
+
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(Err(ParseError::HeaderValueTooLong),
+               config.parse_headers(b"A: 1234\r\n\r\n", &mut headers).map(|s| s.is_complete()));
+}
+
+#[test]
+fn head_parser_with_config() {
+    let mut config = ParserConfig::new();
+    config.max_head_len(64).max_target_len(16);
+
+    let mut parser = RequestHeadParser::with_config(config);
+    let mut headers = [phr_header::default(); 8];
+    assert!(!parser.feed(&REQUEST[..10], &mut headers).unwrap().is_complete());
+    assert!(parser.feed(&REQUEST[10..], &mut headers).unwrap().is_complete());
+
+    let mut parser = RequestHeadParser::with_config(config);
+    assert!(!parser.feed(b"GET /0123456789", &mut headers).unwrap().is_complete());
+    assert_eq!(ParseError::TargetTooLong,
+               parser.feed(b"abcdefg", &mut headers).map(|s| s.is_complete()).unwrap_err());
+
+    let mut parser = RequestHeadParser::with_config(config);
+    assert!(!parser.feed(b"GET / HTTP/1.1\r\n", &mut headers).unwrap().is_complete());
+    assert_eq!(ParseError::HeadTooLarge,
+               parser.feed(&[b'a'; 64], &mut headers).map(|s| s.is_complete()).unwrap_err());
+
+    let mut config = ParserConfig::new();
+    config.strict(true);
+    let mut parser = RequestHeadParser::with_config(config);
+    assert_eq!(ParseError::BareLf,
+               parser.feed(b"GET / HTTP/1.1\n", &mut headers)
+                     .map(|s| s.is_complete())
+                     .unwrap_err());
+}
+
+#[test]
+fn strict_head_parser_fed_in_small_pieces() {
+    let mut head = b"GET / HTTP/1.1\r\n".to_vec();
+    for i in 0..8000 {
+        head.extend_from_slice(format!("X-Header-{}: value\r\n", i).as_bytes());
+    }
+    let mut config = ParserConfig::new();
+    config.strict(true);
+    let mut headers = vec![phr_header::default(); 8000];
+
+    let mut request = head.clone();
+    request.extend_from_slice(b"\r\n");
+    let mut parser = RequestHeadParser::with_config(config);
+    let mut pieces = request.chunks(16).peekable();
+    while let Some(piece) = pieces.next() {
+        let complete = parser.feed(piece, &mut headers).unwrap().is_complete();
+        assert_eq!(pieces.peek().is_none(), complete);
+    }
+
+    let mut request = head;
+    request.extend_from_slice(b"X-Last: value\n\r\n");
+    let mut parser = RequestHeadParser::with_config(config);
+    let error = request.chunks(16)
+                       .map(|piece| parser.feed(piece, &mut headers).map(|_| ()))
+                       .find(Result::is_err);
+    assert_eq!(Some(Err(ParseError::BareLf)), error);
+}
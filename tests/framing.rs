@@ -0,0 +1,115 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn request_framing(buf: &[u8]) -> Result<BodyFraming, ParseError> {
+    let mut headers = [phr_header::default(); 8];
+    match Request::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, req) => BodyFraming::for_request(&req),
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+fn response_framing(buf: &[u8], method: &[u8]) -> Result<BodyFraming, ParseError> {
+    let mut headers = [phr_header::default(); 8];
+    match Response::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, res) => BodyFraming::for_response(&res, Method::from_bytes(method)),
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn request_without_body() {
+    assert_eq!(Ok(BodyFraming::Empty),
+               request_framing(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n"));
+}
+
+#[test]
+fn request_content_length() {
+    assert_eq!(Ok(BodyFraming::ContentLength(42)),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 42\r\n\r\n"));
+    assert_eq!(Ok(BodyFraming::ContentLength(5)),
+               request_framing(b"POST / HTTP/1.1\r\ncontent-length: 5, 5\r\nContent-Length: 5\r\n\r\n"));
+}
+
+#[test]
+fn request_invalid_content_length() {
+    assert_eq!(Err(ParseError::ContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: +5\r\n\r\n"));
+    assert_eq!(Err(ParseError::ContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: \r\n\r\n"));
+    assert_eq!(Err(ParseError::ContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 99999999999999999999\r\n\r\n"));
+    assert_eq!(Err(ParseError::ContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n 5\r\n\r\n"));
+}
+
+#[test]
+fn request_conflicting_content_length() {
+    assert_eq!(Err(ParseError::ConflictingContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\n"));
+    assert_eq!(Err(ParseError::ConflictingContentLength),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 5, 6\r\n\r\n"));
+}
+
+#[test]
+fn request_chunked() {
+    assert_eq!(Ok(BodyFraming::Chunked),
+               request_framing(b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip, Chunked\r\n\r\n"));
+    assert_eq!(Ok(BodyFraming::Chunked),
+               request_framing(b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\
+                                 Transfer-Encoding: chunked\r\n\r\n"));
+    // Transfer-Encoding overrides Content-Length.
+    assert_eq!(Ok(BodyFraming::Chunked),
+               request_framing(b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\
+                                 Transfer-Encoding: chunked\r\n\r\n"));
+}
+
+#[test]
+fn request_chunked_not_final() {
+    assert_eq!(Err(ParseError::TransferEncoding),
+               request_framing(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n"));
+    assert_eq!(Err(ParseError::TransferEncoding),
+               request_framing(b"POST / HTTP/1.1\r\nTransfer-Encoding: \r\n\r\n"));
+}
+
+#[test]
+fn http10_transfer_encoding() {
+    assert_eq!(Err(ParseError::TransferEncoding),
+               request_framing(b"POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\
+                                 Content-Length: 100\r\n\r\n"));
+    assert_eq!(Err(ParseError::TransferEncoding),
+               response_framing(b"HTTP/1.0 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n", b"GET"));
+}
+
+#[test]
+fn response_without_body() {
+    assert_eq!(Ok(BodyFraming::Empty),
+               response_framing(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n", b"HEAD"));
+    assert_eq!(Ok(BodyFraming::Empty),
+               response_framing(b"HTTP/1.1 101 Switching Protocols\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::Empty),
+               response_framing(b"HTTP/1.1 204 No Content\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::Empty),
+               response_framing(b"HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::Empty),
+               response_framing(b"HTTP/1.1 200 Connection Established\r\n\r\n", b"CONNECT"));
+    assert_eq!(Ok(BodyFraming::CloseDelimited),
+               response_framing(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n",
+                                b"CONNECT"));
+}
+
+#[test]
+fn response_with_body() {
+    assert_eq!(Ok(BodyFraming::ContentLength(5)),
+               response_framing(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::Chunked),
+               response_framing(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::CloseDelimited),
+               response_framing(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\n", b"GET"));
+    assert_eq!(Ok(BodyFraming::CloseDelimited),
+               response_framing(b"HTTP/1.0 200 OK\r\n\r\n", b"GET"));
+    assert_eq!(Err(ParseError::ConflictingContentLength),
+               response_framing(b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\nContent-Length: 2\r\n\r\n",
+                                b"GET"));
+}
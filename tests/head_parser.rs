@@ -0,0 +1,67 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+#[test]
+fn byte_at_a_time() {
+    let buf = b"GET /hoge HTTP/1.1\r\nHost: example.com\r\n\r\n";
+    let mut parser = RequestHeadParser::new();
+    let mut headers = [phr_header::default(); 4];
+
+    for &byte in &buf[..buf.len() - 1] {
+        assert!(parser.feed(&[byte], &mut headers).unwrap().is_partial());
+    }
+    match parser.feed(&buf[buf.len() - 1..], &mut headers).unwrap() {
+        Status::Complete(consumed, req) => {
+            assert_eq!(buf.len(), consumed);
+            assert_eq!(b"/hoge", req.path());
+            assert_eq!(1, req.headers().len());
+        }
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn only_rescans_new_bytes() {
+    let mut parser = RequestHeadParser::new();
+    let mut headers = [phr_header::default(); 4];
+    assert!(parser.feed(b"GET / HTTP/1.1\r\nA: b", &mut headers).unwrap().is_partial());
+    assert!(parser.feed(b"\r\n", &mut headers).unwrap().is_partial());
+    assert!(parser.feed(b"\r", &mut headers).unwrap().is_partial());
+    assert!(parser.feed(b"\n", &mut headers).unwrap().is_complete());
+}
+
+#[test]
+fn head_is_validated_once_complete() {
+    // Until the end of the head arrives only the new bytes are scanned for
+    // it, so the bad byte is reported when the head is finally parsed.
+    let mut parser = RequestHeadParser::new();
+    let mut headers = [phr_header::default(); 4];
+    assert!(parser.feed(b"GET / HTTP/1.1\r\n", &mut headers).unwrap().is_partial());
+    assert!(parser.feed(b"A: \x01", &mut headers).unwrap().is_partial());
+    assert_eq!(ParseError::Malformed,
+               parser.feed(b"\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn leftover_and_consume() {
+    let mut parser = RequestHeadParser::new();
+    let mut headers = [phr_header::default(); 4];
+    let consumed = match parser.feed(b"POST / HTTP/1.1\r\n\r\nbodyGET /next HTTP/1.1\r\n\r\n",
+                                     &mut headers)
+        .unwrap() {
+        Status::Complete(consumed, _) => consumed,
+        Status::Partial => panic!("unexpected partial"),
+    };
+    assert_eq!(b"bodyGET /next HTTP/1.1\r\n\r\n", &parser.buffer()[consumed..]);
+
+    parser.consume(consumed + 4);
+    match parser.feed(b"", &mut headers).unwrap() {
+        Status::Complete(_, req) => assert_eq!(b"/next", req.path()),
+        Status::Partial => panic!("unexpected partial"),
+    }
+
+    parser.reset();
+    assert!(parser.buffer().is_empty());
+    assert!(parser.into_buffer().is_empty());
+}
@@ -0,0 +1,92 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn request_with_headers(count: usize) -> Vec<u8> {
+    let mut buf = b"GET / HTTP/1.1\r\n".to_vec();
+    for i in 0..count {
+        buf.extend_from_slice(format!("X-Header-{}: {}\r\n", i, i).as_bytes());
+    }
+    buf.extend_from_slice(b"\r\n");
+    buf
+}
+
+#[test]
+fn fits_inline() {
+    let buf = request_with_headers(3);
+    let mut headers = HeaderBuffer::new();
+    match Request::parse_growing(&buf, &mut headers).unwrap() {
+        Status::Complete(consumed, req) => {
+            assert_eq!(buf.len(), consumed);
+            assert_eq!(3, req.headers().len());
+        }
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn grows_onto_heap() {
+    let buf = request_with_headers(80);
+    let mut headers = HeaderBuffer::new();
+    match Request::parse_growing(&buf, &mut headers).unwrap() {
+        Status::Complete(_, req) => {
+            assert_eq!(80, req.headers().len());
+            let last = req.headers().iter().next_back().unwrap();
+            assert_eq!(b"X-Header-79", last.name());
+            assert_eq!(b"79", last.value());
+        }
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn ceiling() {
+    let buf = request_with_headers(101);
+    let mut headers = HeaderBuffer::new();
+    assert_eq!(ParseError::TooManyHeaders { needed: 101 },
+               Request::parse_growing(&buf, &mut headers).unwrap_err());
+
+    let buf = request_with_headers(5);
+    let mut headers = HeaderBuffer::with_max_headers(4);
+    assert_eq!(4, headers.max_headers());
+    assert_eq!(ParseError::TooManyHeaders { needed: 5 },
+               Request::parse_growing(&buf, &mut headers).unwrap_err());
+}
+
+#[test]
+fn partial_head_beyond_inline_storage() {
+    let buf = request_with_headers(40);
+    let mut headers = HeaderBuffer::new();
+    let status = Request::parse_growing(&buf[..buf.len() - 2], &mut headers).unwrap();
+    assert!(status.is_partial());
+}
+
+#[test]
+fn malformed_after_growing() {
+    let mut buf = request_with_headers(40);
+    let len = buf.len();
+    buf[len - 5] = b'\x7f';
+    let mut headers = HeaderBuffer::new();
+    assert_eq!(ParseError::Malformed,
+               Request::parse_growing(&buf, &mut headers).unwrap_err());
+}
+
+#[test]
+fn response_and_headers() {
+    let mut buf = b"HTTP/1.1 200 OK\r\n".to_vec();
+    let header_start = buf.len();
+    for i in 0..50 {
+        buf.extend_from_slice(format!("X-{}: y\r\n", i).as_bytes());
+    }
+    buf.extend_from_slice(b"\r\n");
+
+    let mut headers = HeaderBuffer::new();
+    match Response::parse_growing(&buf, &mut headers).unwrap() {
+        Status::Complete(_, res) => assert_eq!(50, res.headers().len()),
+        Status::Partial => panic!("unexpected partial"),
+    }
+    match Headers::parse_growing(&buf[header_start..], &mut headers).unwrap() {
+        Status::Complete(_, parsed) => assert_eq!(50, parsed.len()),
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
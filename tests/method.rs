@@ -0,0 +1,87 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+const REGISTERED: &[(&[u8], Method<'static>)] = &[(b"GET", Method::Get),
+                                                  (b"HEAD", Method::Head),
+                                                  (b"POST", Method::Post),
+                                                  (b"PUT", Method::Put),
+                                                  (b"DELETE", Method::Delete),
+                                                  (b"CONNECT", Method::Connect),
+                                                  (b"OPTIONS", Method::Options),
+                                                  (b"TRACE", Method::Trace),
+                                                  (b"PATCH", Method::Patch)];
+
+#[test]
+fn from_bytes() {
+    for &(bytes, method) in REGISTERED {
+        assert_eq!(method, Method::from_bytes(bytes));
+        assert_eq!(bytes, method.as_bytes());
+    }
+    assert_eq!(Method::Extension(b"PROPFIND"), Method::from_bytes(b"PROPFIND"));
+    assert_eq!(Method::Extension(b"get"), Method::from_bytes(b"get"));
+    assert_eq!(b"get", Method::from_bytes(b"get").as_bytes());
+    assert_eq!("PATCH", Method::Patch.to_string());
+}
+
+#[test]
+fn properties() {
+    let safe: Vec<_> = REGISTERED.iter().filter(|m| m.1.is_safe()).map(|m| m.0).collect();
+    assert_eq!(vec![&b"GET"[..], b"HEAD", b"OPTIONS", b"TRACE"], safe);
+
+    let idempotent: Vec<_> = REGISTERED.iter().filter(|m| m.1.is_idempotent()).map(|m| m.0).collect();
+    assert_eq!(vec![&b"GET"[..], b"HEAD", b"PUT", b"DELETE", b"OPTIONS", b"TRACE"],
+               idempotent);
+
+    let body: Vec<_> = REGISTERED.iter().filter(|m| m.1.allows_request_body()).map(|m| m.0).collect();
+    assert_eq!(vec![&b"POST"[..], b"PUT", b"OPTIONS", b"PATCH"], body);
+
+    let extension = Method::Extension(b"PROPFIND");
+    assert!(!extension.is_safe());
+    assert!(!extension.is_idempotent());
+    assert!(extension.allows_request_body());
+}
+
+#[test]
+fn from_request() {
+    let mut headers = [phr_header::default(); 4];
+    match Request::parse(b"MKCOL /x HTTP/1.1\r\n\r\n", &mut headers).unwrap() {
+        Status::Complete(_, req) => {
+            assert_eq!(Method::Extension(b"MKCOL"), req.method());
+            assert_eq!(b"MKCOL", req.method_bytes());
+        }
+        Status::Partial => panic!("partial"),
+    }
+}
+
+#[test]
+fn extension_with_registered_name() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let hash = |method: &Method| {
+        let mut hasher = DefaultHasher::new();
+        method.hash(&mut hasher);
+        hasher.finish()
+    };
+    for &(bytes, method) in REGISTERED {
+        let extension = Method::Extension(bytes);
+        assert_eq!(method, extension);
+        assert_eq!(hash(&method), hash(&extension));
+        assert_eq!(method.is_safe(), extension.is_safe());
+        assert_eq!(method.is_idempotent(), extension.is_idempotent());
+        assert_eq!(method.allows_request_body(), extension.allows_request_body());
+    }
+    assert_ne!(Method::Get, Method::Extension(b"get"));
+
+    let mut headers = [phr_header::default(); 4];
+    match Response::parse(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n", &mut headers).unwrap() {
+        Status::Complete(_, res) => {
+            assert_eq!(Ok(BodyFraming::Empty),
+                       BodyFraming::for_response(&res, Method::Extension(b"HEAD")));
+        }
+        Status::Partial => panic!("partial"),
+    }
+    assert!(RequestTarget::parse(b"example.com:443", Method::Extension(b"CONNECT")).is_ok());
+    assert!(RequestTarget::parse(b"*", Method::Extension(b"OPTIONS")).is_ok());
+}
@@ -0,0 +1,154 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+#[test]
+fn simple() {
+    let buf = b"Host: example.com\r\nCookie: \r\n\r\nbody";
+    let mut headers = [phr_header::default(); 4];
+    match Headers::parse(buf, &mut headers).unwrap() {
+        Status::Complete(consumed, parsed) => {
+            assert_eq!(buf.len() - 4, consumed);
+            assert_eq!(2, parsed.len());
+            let parsed: Vec<_> = parsed.iter().collect();
+            assert_eq!(b"Host", parsed[0].name());
+            assert_eq!(b"example.com", parsed[0].value());
+            assert_eq!(b"Cookie", parsed[1].name());
+            assert_eq!(b"", parsed[1].value());
+        }
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn partial() {
+    let mut headers = [phr_header::default(); 4];
+    let status = Headers::parse(b"Host: example.com\r\nCookie: \r\n\r", &mut headers).unwrap();
+    assert!(status.is_partial());
+}
+
+#[test]
+fn malformed() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(ParseError::Malformed,
+               Headers::parse(b"Host: e\x7fample.com\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn status_map() {
+    assert_eq!(Status::Complete(3, 2), Status::Complete(3, 1).map(|v| v + 1));
+    assert_eq!(Status::Partial, Status::Partial.map(|v: i32| v + 1));
+}
+
+#[test]
+fn too_many_headers() {
+    let buf = b"A: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+    let mut headers = [phr_header::default(); 2];
+    assert_eq!(ParseError::TooManyHeaders { needed: 3 },
+               Headers::parse(buf, &mut headers).unwrap_err());
+
+    let mut headers = [];
+    assert_eq!(ParseError::TooManyHeaders { needed: 3 },
+               Headers::parse(buf, &mut headers).unwrap_err());
+}
+
+#[test]
+fn lookup() {
+    let buf = b"Host: example.com\r\nAccept: text/html\r\nX-Forwarded-For: a\r\n\
+                accept: */*\r\nx-forwarded-for: b\r\n\r\n";
+    let mut headers = [phr_header::default(); 8];
+    let parsed = match Headers::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, parsed) => parsed,
+        Status::Partial => panic!("unexpected partial"),
+    };
+
+    assert_eq!(Some(&b"example.com"[..]), parsed.get(b"HOST"));
+    assert_eq!(Some(&b"text/html"[..]), parsed.get(b"accept"));
+    assert_eq!(None, parsed.get(b"Cookie"));
+    assert!(parsed.contains(b"x-forwarded-for"));
+    assert!(!parsed.contains(b"X-Forwarded"));
+
+    let all: Vec<_> = parsed.get_all(b"X-FORWARDED-FOR").collect();
+    assert_eq!(vec![&b"a"[..], &b"b"[..]], all);
+
+    // Original order and casing are kept for forwarding.
+    let names: Vec<_> = parsed.iter().map(|h| h.name()).collect();
+    assert_eq!(vec![&b"Host"[..],
+                    &b"Accept"[..],
+                    &b"X-Forwarded-For"[..],
+                    &b"accept"[..],
+                    &b"x-forwarded-for"[..]],
+               names);
+}
+
+#[test]
+fn lookup_skips_continuation_lines() {
+    let buf = b"foo: b\r\n  \tc\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let parsed = match Headers::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, parsed) => parsed,
+        Status::Partial => panic!("unexpected partial"),
+    };
+    assert_eq!(vec![&b"b"[..]], parsed.get_all(b"foo").collect::<Vec<_>>());
+    assert!(!parsed.contains(b""));
+}
+
+fn parse<'buf, 'h>(buf: &'buf [u8], headers: &'h mut [phr_header]) -> Headers<'buf, 'h> {
+    match Headers::parse(buf, headers).unwrap() {
+        Status::Complete(_, parsed) => parsed,
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn fields_group_continuation_lines() {
+    let buf = b"foo: \r\nfoo: b \r\n  \tc\r\n d\r\nbar: e\r\n\r\n";
+    let mut headers = [phr_header::default(); 8];
+    let parsed = parse(buf, &mut headers);
+    assert_eq!(5, parsed.len());
+
+    let fields: Vec<_> = parsed.fields().collect();
+    assert_eq!(3, fields.len());
+
+    assert_eq!(b"foo", fields[0].name());
+    assert!(!fields[0].is_folded());
+    assert_eq!(b"", &fields[0].value()[..]);
+
+    assert_eq!(b"foo", fields[1].name());
+    assert!(fields[1].is_folded());
+    assert_eq!(vec![&b"b "[..], &b"  \tc"[..], &b" d"[..]],
+               fields[1].segments().collect::<Vec<_>>());
+    assert_eq!(b"b c d", &fields[1].value()[..]);
+
+    assert_eq!(b"bar", fields[2].name());
+    assert_eq!(vec![&b"e"[..]], fields[2].segments().collect::<Vec<_>>());
+}
+
+#[test]
+fn unfolded_value_is_borrowed() {
+    let buf = b"foo: bar\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let parsed = parse(buf, &mut headers);
+    match parsed.fields().next().unwrap().value() {
+        ::std::borrow::Cow::Borrowed(value) => assert_eq!(b"bar", value),
+        ::std::borrow::Cow::Owned(_) => panic!("unexpected allocation"),
+    }
+}
+
+#[test]
+fn lookup_truncates_folded_values() {
+    let buf = b"X: a\r\n b\r\nX: c\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let parsed = parse(buf, &mut headers);
+    assert_eq!(Some(&b"a"[..]), parsed.get(b"x"));
+    assert_eq!(vec![&b"a"[..], &b"c"[..]], parsed.get_all(b"x").collect::<Vec<_>>());
+    assert_eq!(b"a b", &parsed.fields().next().unwrap().value()[..]);
+}
+
+#[test]
+fn reject_obs_fold() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(Ok(()), parse(b"foo: b\r\n\r\n", &mut headers).reject_obs_fold());
+    assert_eq!(Err(ParseError::ObsFold),
+               parse(b"foo: b\r\n c\r\n\r\n", &mut headers).reject_obs_fold());
+}
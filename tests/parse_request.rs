@@ -0,0 +1,163 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn complete<T>(status: Result<Status<T>, ParseError>) -> (usize, T) {
+    match status {
+        Ok(Status::Complete(consumed, value)) => (consumed, value),
+        Ok(Status::Partial) => panic!("unexpected partial"),
+        Err(e) => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn simple() {
+    let buf = b"GET / HTTP/1.0\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let (consumed, req) = complete(Request::parse(buf, &mut headers));
+    assert_eq!(buf.len(), consumed);
+    assert_eq!(b"GET", req.method_bytes());
+    assert_eq!(Method::Get, req.method());
+    assert_eq!(b"/", req.path());
+    assert_eq!(0, req.minor_version());
+    assert!(req.headers().is_empty());
+}
+
+#[test]
+fn parse_headers() {
+    let buf = b"GET /hoge HTTP/1.1\r\nHost: example.com\r\nCookie: \r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let (consumed, req) = complete(Request::parse(buf, &mut headers));
+    assert_eq!(buf.len(), consumed);
+    assert_eq!(1, req.minor_version());
+    assert_eq!(b"/hoge", req.path());
+
+    let parsed: Vec<_> = req.headers().iter().collect();
+    assert_eq!(2, parsed.len());
+    assert_eq!(b"Host", parsed[0].name());
+    assert_eq!(b"example.com", parsed[0].value());
+    assert_eq!(b"Cookie", parsed[1].name());
+    assert_eq!(b"", parsed[1].value());
+}
+
+#[test]
+fn parse_multiline() {
+    let buf = b"GET / HTTP/1.0\r\nfoo: \r\nfoo: b\r\n  \tc\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let (_, req) = complete(Request::parse(buf, &mut headers));
+
+    let parsed: Vec<_> = req.headers().into_iter().collect();
+    assert_eq!(3, parsed.len());
+    assert!(!parsed[1].is_continuation());
+    assert!(parsed[2].is_continuation());
+    assert_eq!(b"", parsed[2].name());
+    assert_eq!(b"  \tc", parsed[2].value());
+}
+
+#[test]
+fn request_outlives_header_storage_borrow() {
+    let buf = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec();
+    let mut headers = [phr_header::default(); 4];
+    let host = {
+        let (_, req) = complete(Request::parse(&buf, &mut headers));
+        req.headers().iter().next().unwrap().value()
+    };
+    let (_, req) = complete(Request::parse(b"GET /again HTTP/1.1\r\n\r\n", &mut headers));
+    assert_eq!(b"/again", req.path());
+    assert_eq!(b"example.com", host);
+}
+
+#[test]
+fn partial() {
+    let mut headers = [phr_header::default(); 4];
+    assert!(Request::parse(b"GET / HTTP/1.0\r\n\r", &mut headers).unwrap().is_partial());
+}
+
+#[test]
+fn malformed() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(ParseError::Malformed,
+               Request::parse(b"G\0T / HTTP/1.0\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn oversized_minor_version() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(ParseError::Version,
+               Request::parse(b"GET / HTTP/1.256\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn slowloris() {
+    let buf = b"GET /hoge HTTP/1.0\r\n\r";
+    let mut headers = [phr_header::default(); 4];
+    assert!(Request::parse_with_last_len(buf, buf.len() - 1, &mut headers)
+        .unwrap()
+        .is_partial());
+
+    let buf = b"GET /hoge HTTP/1.0\r\n\r\n";
+    let (consumed, _) =
+        complete(Request::parse_with_last_len(buf, buf.len() - 1, &mut headers));
+    assert_eq!(buf.len(), consumed);
+}
+
+#[test]
+fn error_display() {
+    assert_eq!("malformed HTTP message", ParseError::Malformed.to_string());
+}
+
+#[test]
+fn too_many_headers() {
+    let buf = b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+    let mut headers = [phr_header::default(); 2];
+    assert_eq!(ParseError::TooManyHeaders { needed: 3 },
+               Request::parse(buf, &mut headers).unwrap_err());
+
+    let mut headers = [phr_header::default(); 3];
+    let (_, req) = complete(Request::parse(buf, &mut headers));
+    assert_eq!(3, req.headers().len());
+}
+
+#[test]
+fn too_many_headers_counts_continuation_lines() {
+    let buf = b"GET / HTTP/1.1\r\nA: 1\r\n  2\r\nB: 3\r\n\r\n";
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(ParseError::TooManyHeaders { needed: 3 },
+               Request::parse(buf, &mut headers).unwrap_err());
+}
+
+#[test]
+fn too_many_headers_in_partial_head() {
+    let buf = b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC";
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(ParseError::TooManyHeaders { needed: 3 },
+               Request::parse(buf, &mut headers).unwrap_err());
+}
+
+#[test]
+fn too_many_headers_without_storage() {
+    let mut headers = [];
+    assert_eq!(ParseError::TooManyHeaders { needed: 2 },
+               Request::parse(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n", &mut headers)
+                   .unwrap_err());
+    assert_eq!(ParseError::Malformed,
+               Request::parse(b"GET / HTTP/1.1x\r\nA: 1\r\n\r\n", &mut headers).unwrap_err());
+    assert!(Request::parse(b"GET / HTTP/1.1\r\n\r\n", &mut headers).unwrap().is_complete());
+}
+
+#[test]
+fn malformed_terminator_with_full_storage() {
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(ParseError::Malformed,
+               Request::parse(b"GET / HTTP/1.1\r\nA: 1\r\n\rX", &mut headers).unwrap_err());
+}
+
+#[test]
+fn malformed_line_after_full_storage() {
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(ParseError::Malformed,
+               Request::parse(b"GET / HTTP/1.1\r\nA: b\r\n:bad\r\n\r\n", &mut headers).unwrap_err());
+    assert_eq!(ParseError::Malformed,
+               Request::parse(b"GET / HTTP/1.1\r\nA: b\r\nC: d\r\n:bad\r\n\r\n", &mut headers)
+                   .unwrap_err());
+}
@@ -0,0 +1,78 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn complete<T>(status: Result<Status<T>, ParseError>) -> (usize, T) {
+    match status {
+        Ok(Status::Complete(consumed, value)) => (consumed, value),
+        Ok(Status::Partial) => panic!("unexpected partial"),
+        Err(e) => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn simple() {
+    let buf = b"HTTP/1.0 200 OK\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let (consumed, res) = complete(Response::parse(buf, &mut headers));
+    assert_eq!(buf.len(), consumed);
+    assert_eq!(0, res.minor_version());
+    assert_eq!(200, res.status());
+    assert_eq!(b"OK", res.reason());
+    assert!(res.headers().is_empty());
+}
+
+#[test]
+fn parse_headers() {
+    let buf = b"HTTP/1.1 500 Internal Server Error\r\nHost: example.com\r\nCookie: \r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let (consumed, res) = complete(Response::parse(buf, &mut headers));
+    assert_eq!(buf.len(), consumed);
+    assert_eq!(1, res.minor_version());
+    assert_eq!(500, res.status());
+    assert_eq!(b"Internal Server Error", res.reason());
+
+    let parsed: Vec<_> = res.headers().iter().collect();
+    assert_eq!(2, parsed.len());
+    assert_eq!(b"Host", parsed[0].name());
+    assert_eq!(b"example.com", parsed[0].value());
+    assert_eq!(b"Cookie", parsed[1].name());
+    assert_eq!(b"", parsed[1].value());
+}
+
+#[test]
+fn partial() {
+    let mut headers = [phr_header::default(); 4];
+    assert!(Response::parse(b"HTTP/1.1 200 OK\r\nA: 1\r\n", &mut headers).unwrap().is_partial());
+}
+
+#[test]
+fn malformed() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(ParseError::Malformed,
+               Response::parse(b"HTTP/1.1 OK\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn oversized_status() {
+    let mut headers = [phr_header::default(); 4];
+    assert_eq!(ParseError::StatusCode,
+               Response::parse(b"HTTP/1.1 65536 OK\r\n\r\n", &mut headers).unwrap_err());
+}
+
+#[test]
+fn slowloris() {
+    let buf = b"HTTP/1.0 200 OK \r\n\r";
+    let mut headers = [phr_header::default(); 4];
+    assert!(Response::parse_with_last_len(buf, buf.len() - 1, &mut headers)
+        .unwrap()
+        .is_partial());
+}
+
+#[test]
+fn too_many_headers() {
+    let buf = b"HTTP/1.1 200 OK\nA: 1\nB: 2\n\n";
+    let mut headers = [phr_header::default(); 1];
+    assert_eq!(ParseError::TooManyHeaders { needed: 2 },
+               Response::parse(buf, &mut headers).unwrap_err());
+}
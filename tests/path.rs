@@ -0,0 +1,91 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+use std::borrow::Cow;
+
+use PathAnomaly::*;
+
+fn check(path: &[u8], expected: &[u8], anomalies: &[PathAnomaly]) {
+    let normalized = normalize_path(path);
+    assert_eq!(String::from_utf8_lossy(expected),
+               String::from_utf8_lossy(normalized.path()),
+               "normalizing {:?}",
+               String::from_utf8_lossy(path));
+    assert_eq!(anomalies, normalized.anomalies(), "normalizing {:?}", String::from_utf8_lossy(path));
+}
+
+#[test]
+fn clean_paths_are_borrowed() {
+    for path in &[&b"/"[..], b"/index.html", b"/a/b/", b"/caf\xc3\xa9"] {
+        let normalized = normalize_path(path);
+        match normalized.into_path() {
+            Cow::Borrowed(normalized) => assert_eq!(path, &normalized),
+            Cow::Owned(_) => panic!("allocated for {:?}", path),
+        }
+    }
+}
+
+#[test]
+fn decodes_percent_escapes() {
+    check(b"/a%20b/%7Euser", b"/a b/~user", &[]);
+    check(b"/caf%C3%A9", b"/caf\xc3\xa9", &[]);
+    check(b"/a%zz/b%4", b"/a%25zz/b%254", &[InvalidEscape]);
+}
+
+#[test]
+fn percent_stays_encoded() {
+    check(b"/100%25", b"/100%25", &[]);
+    check(b"/a%252Fb", b"/a%252Fb", &[]);
+    check(b"/a%2500", b"/a%2500", &[]);
+    check(b"/a%%32%46b", b"/a%252Fb", &[InvalidEscape]);
+    check(b"/a%2Fb", b"/a%2Fb", &[EncodedSlash]);
+}
+
+#[test]
+fn removes_dot_segments() {
+    check(b"/a/./b/../c", b"/a/c", &[DotSegment]);
+    check(b"/a/b/..", b"/a/", &[DotSegment]);
+    check(b"/a/.", b"/a/", &[DotSegment]);
+    check(b"/a/%2e%2E/b", b"/b", &[DotSegment]);
+    check(b"/a/.../b", b"/a/.../b", &[]);
+}
+
+#[test]
+fn collapses_duplicate_slashes() {
+    check(b"//a///b//", b"/a/b/", &[DuplicateSlash]);
+    check(b"//", b"/", &[DuplicateSlash]);
+}
+
+#[test]
+fn traversal_above_root() {
+    check(b"/../etc/passwd", b"/etc/passwd", &[TraversalAboveRoot]);
+    check(b"/a/../../b", b"/b", &[DotSegment, TraversalAboveRoot]);
+    check(b"/%2e%2e/%2e%2e/x", b"/x", &[TraversalAboveRoot]);
+    assert!(!normalize_path(b"/../x").is_safe());
+}
+
+#[test]
+fn encoded_separators_and_nul() {
+    check(b"/a%2Fb/c", b"/a%2Fb/c", &[EncodedSlash]);
+    check(b"/a%5c..%5cb", b"/a%5c..%5cb", &[EncodedSlash]);
+    check(b"/file%00.txt", b"/file%00.txt", &[Nul]);
+    check(b"/file\0.txt", b"/file%00.txt", &[Nul]);
+    assert!(!normalize_path(b"/a%2fb").is_safe());
+    assert!(!normalize_path(b"/a%00").is_safe());
+    assert!(normalize_path(b"/a/../b//c").is_safe());
+}
+
+#[test]
+fn backslash_is_a_separator() {
+    check(b"/a\\b", b"/a/b", &[Backslash]);
+    check(b"/a\\..\\..\\etc", b"/etc", &[Backslash, DotSegment, TraversalAboveRoot]);
+    assert!(!normalize_path(b"/a\\..\\..\\etc").is_safe());
+}
+
+#[test]
+fn relative_and_query() {
+    check(b"a/b", b"/a/b", &[NotAbsolute]);
+    check(b"", b"/", &[NotAbsolute]);
+    check(b"/a/../b?x=/../y", b"/b", &[DotSegment]);
+    check(b"/a?", b"/a", &[]);
+}
@@ -0,0 +1,99 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+#[test]
+fn back_to_back() {
+    let buf = b"GET /a HTTP/1.1\r\nHost: x\r\n\r\n\
+                POST /b HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+                POST /c HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nworld\r\n0\r\n\r\n\
+                GET /d HTTP/1.1\r\n\r\n";
+    let mut headers = [phr_header::default(); 8];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+
+    let requests: Vec<_> = pipeline.by_ref().map(Result::unwrap).collect();
+    assert_eq!(4, requests.len());
+
+    assert_eq!(b"/a", requests[0].request().path());
+    assert_eq!(Some(&b"x"[..]), requests[0].request().headers().get(b"host"));
+    assert_eq!(BodyFraming::Empty, requests[0].framing());
+    assert_eq!(b"", requests[0].body());
+
+    assert_eq!(b"/b", requests[1].request().path());
+    assert_eq!(BodyFraming::ContentLength(5), requests[1].framing());
+    assert_eq!(b"hello", requests[1].body());
+
+    assert_eq!(b"/c", requests[2].request().path());
+    assert_eq!(BodyFraming::Chunked, requests[2].framing());
+    assert_eq!(b"5\r\nworld\r\n0\r\n\r\n", requests[2].body());
+
+    assert_eq!(b"/d", requests[3].request().path());
+
+    assert_eq!(buf.len(), pipeline.offset());
+    assert!(pipeline.remainder().is_empty());
+}
+
+#[test]
+fn stops_at_partial_head() {
+    let buf = b"GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\nHo";
+    let mut headers = [phr_header::default(); 4];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+
+    assert_eq!(b"/a", pipeline.next().unwrap().unwrap().request().path());
+    assert!(pipeline.next().is_none());
+    assert_eq!(19, pipeline.offset());
+    assert_eq!(b"GET /b HTTP/1.1\r\nHo", pipeline.remainder());
+}
+
+#[test]
+fn stops_at_partial_body() {
+    let buf = b"POST /a HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello";
+    let mut headers = [phr_header::default(); 4];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert!(pipeline.next().is_none());
+    assert_eq!(0, pipeline.offset());
+
+    let buf = b"POST /a HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert!(pipeline.next().is_none());
+    assert_eq!(&buf[..], pipeline.remainder());
+}
+
+#[test]
+fn header_storage_is_shared() {
+    let buf = b"GET /a HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\nGET /b HTTP/1.1\r\nC: 3\r\n\r\n";
+    let mut headers = [phr_header::default(); 2];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert_eq!(2, pipeline.next().unwrap().unwrap().request().headers().len());
+    assert_eq!(Some(Err(ParseError::TooManyHeaders { needed: 3 })), pipeline.next().map(|r| {
+        r.map(|p| p.request().path())
+    }));
+
+    let mut headers = vec![phr_header::default(); 3];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert_eq!(2, pipeline.by_ref().map(Result::unwrap).count());
+    assert_eq!(buf.len(), pipeline.offset());
+}
+
+#[test]
+fn error_ends_iteration() {
+    let buf = b"POST /a HTTP/1.1\r\nContent-Length: 1\r\nContent-Length: 2\r\n\r\nxGET / HTTP/1.1\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert_eq!(ParseError::ConflictingContentLength,
+               pipeline.next().unwrap().unwrap_err());
+    assert!(pipeline.next().is_none());
+    assert_eq!(0, pipeline.offset());
+}
+
+#[test]
+fn http10_transfer_encoding_is_rejected() {
+    let buf = b"POST /a HTTP/1.0\r\nTransfer-Encoding: chunked\r\nContent-Length: 100\r\n\r\n\
+                0\r\n\r\nGET /smuggled HTTP/1.1\r\nHost: x\r\n\r\n";
+    let mut headers = [phr_header::default(); 4];
+    let mut pipeline = Pipeline::new(buf, &mut headers);
+    assert_eq!(ParseError::TransferEncoding, pipeline.next().unwrap().unwrap_err());
+    assert!(pipeline.next().is_none());
+    assert_eq!(0, pipeline.offset());
+}
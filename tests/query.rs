@@ -0,0 +1,67 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+use std::borrow::Cow;
+
+fn pairs(query: Query) -> Vec<(Vec<u8>, Vec<u8>)> {
+    query.iter().map(|(k, v)| (k.decode().into_owned(), v.decode().into_owned())).collect()
+}
+
+#[test]
+fn splits_pairs() {
+    let query = Query::from_path(b"/search?q=rust&page=2&&flag&empty=");
+    assert_eq!(b"q=rust&page=2&&flag&empty=", query.as_bytes());
+    assert_eq!(vec![(b"q".to_vec(), b"rust".to_vec()),
+                    (b"page".to_vec(), b"2".to_vec()),
+                    (b"flag".to_vec(), b"".to_vec()),
+                    (b"empty".to_vec(), b"".to_vec())],
+               pairs(query));
+
+    assert_eq!(0, Query::from_path(b"/search").iter().count());
+    assert_eq!(0, Query::from_path(b"/search?").iter().count());
+    assert_eq!(1, Query::new(b"a=b=c").iter().count());
+    assert_eq!(Some(&b"b=c"[..]), Query::new(b"a=b=c").get(b"a").map(|v| v.as_bytes()));
+}
+
+#[test]
+fn lazy_decoding() {
+    let query = Query::new(b"name=J%C3%B6rg+M%FCller&plain=abc&bad=%zz%4");
+    let (key, value) = query.iter().next().unwrap();
+    assert_eq!(Cow::Borrowed(&b"name"[..]), key.decode());
+    assert_eq!(b"J%C3%B6rg+M%FCller", value.as_bytes());
+    assert_eq!(b"J\xc3\xb6rg M\xfcller", &value.decode()[..]);
+    assert_eq!("Jörg M\u{fffd}ller", value.decode_utf8_lossy());
+
+    match query.get(b"plain").unwrap().decode() {
+        Cow::Borrowed(value) => assert_eq!(b"abc", value),
+        Cow::Owned(_) => panic!("allocated"),
+    }
+    assert_eq!(b"%zz%4", &query.get(b"bad").unwrap().decode()[..]);
+}
+
+#[test]
+fn repeated_keys() {
+    let query = Query::new(b"tag=a&x=1&tag=b&t%61g=c&TAG=d");
+    let tags: Vec<_> = query.get_all(b"tag").map(|v| v.decode().into_owned()).collect();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()], tags);
+    assert_eq!(Some(&b"a"[..]), query.get(b"tag").map(|v| v.as_bytes()));
+    assert!(query.contains(b"x"));
+    assert!(!query.contains(b"y"));
+
+    let query = Query::new(b"a+b=1&a%20b=2");
+    assert_eq!(2, query.get_all(b"a b").count());
+    assert!(query.iter().next().unwrap().0.decodes_to(b"a b"));
+}
+
+#[test]
+fn from_request() {
+    let mut headers = [phr_header::default(); 4];
+    match Request::parse(b"GET /p?a=1&b=%2F HTTP/1.1\r\n\r\n", &mut headers).unwrap() {
+        Status::Complete(_, req) => {
+            assert_eq!(b"/", &req.query().get(b"b").unwrap().decode()[..]);
+            let keys: Vec<_> = req.query().into_iter().map(|(k, _)| k.as_bytes()).collect();
+            assert_eq!(vec![&b"a"[..], &b"b"[..]], keys);
+        }
+        Status::Partial => panic!("partial"),
+    }
+}
@@ -0,0 +1,253 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+use std::io;
+
+fn encode(method: &[u8],
+          target: &[u8],
+          minor_version: u8,
+          headers: &[(&str, &str)])
+          -> Result<Vec<u8>, SerializeError> {
+    let mut out = Vec::new();
+    encode_request(&mut out, method, target, minor_version, headers.iter().cloned())?;
+    Ok(out)
+}
+
+#[test]
+fn request_head() {
+    let out = encode(b"GET", b"/index.html", 1, &[("Host", "example.com"), ("Accept", "*/*")])
+        .unwrap();
+    assert_eq!(&b"GET /index.html HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n"[..],
+               &out[..]);
+
+    let out = encode(b"OPTIONS", b"*", 0, &[]).unwrap();
+    assert_eq!(&b"OPTIONS * HTTP/1.0\r\n\r\n"[..], &out[..]);
+}
+
+#[test]
+fn round_trip() {
+    let out = encode(b"POST",
+                     b"/a?b=c%20d",
+                     1,
+                     &[("Content-Length", "0"), ("X-Empty", ""), ("X-Tab", "a\tb")])
+        .unwrap();
+
+    let mut headers = [phr_header::default(); 4];
+    match Request::parse(&out, &mut headers).unwrap() {
+        Status::Complete(consumed, req) => {
+            assert_eq!(out.len(), consumed);
+            assert_eq!(Method::Post, req.method());
+            assert_eq!(b"/a?b=c%20d", req.path());
+            assert_eq!(1, req.minor_version());
+            let headers: Vec<_> = req.headers().iter().map(|h| (h.name(), h.value())).collect();
+            assert_eq!(vec![(&b"Content-Length"[..], &b"0"[..]),
+                            (&b"X-Empty"[..], &b""[..]),
+                            (&b"X-Tab"[..], &b"a\tb"[..])],
+                       headers);
+        }
+        Status::Partial => panic!("partial"),
+    }
+}
+
+#[test]
+fn rejects_injection() {
+    assert_eq!(Err(SerializeError::Method), encode(b"", b"/", 1, &[]));
+    assert_eq!(Err(SerializeError::Method), encode(b"GET /x", b"/", 1, &[]));
+    assert_eq!(Err(SerializeError::Method), encode(b"GET\r\n", b"/", 1, &[]));
+    assert_eq!(Err(SerializeError::Target), encode(b"GET", b"", 1, &[]));
+    assert_eq!(Err(SerializeError::Target), encode(b"GET", b"/ HTTP/1.1\r\nX: y", 1, &[]));
+    assert_eq!(Err(SerializeError::Target), encode(b"GET", b"/\0", 1, &[]));
+    assert_eq!(Err(SerializeError::Version), encode(b"GET", b"/", 10, &[]));
+    assert_eq!(Err(SerializeError::HeaderName), encode(b"GET", b"/", 1, &[("", "x")]));
+    assert_eq!(Err(SerializeError::HeaderName), encode(b"GET", b"/", 1, &[("A B", "x")]));
+    assert_eq!(Err(SerializeError::HeaderName), encode(b"GET", b"/", 1, &[("A:", "x")]));
+    assert_eq!(Err(SerializeError::HeaderValue),
+               encode(b"GET", b"/", 1, &[("A", "x\r\nInjected: 1")]));
+    assert_eq!(Err(SerializeError::HeaderValue), encode(b"GET", b"/", 1, &[("A", "x\ny")]));
+    assert_eq!(Err(SerializeError::HeaderValue), encode(b"GET", b"/", 1, &[("A", "x\0")]));
+    assert_eq!(Err(SerializeError::HeaderValue), encode(b"GET", b"/", 1, &[("A", " x")]));
+    assert_eq!(Err(SerializeError::HeaderValue), encode(b"GET", b"/", 1, &[("A", "x\t")]));
+}
+
+#[test]
+fn error_leaves_buffer_untouched() {
+    let mut out = b"previous".to_vec();
+    let result = encode_request(&mut out, b"GET", b"/", 1, vec![("A", "1"), ("B", "\n")]);
+    assert_eq!(Err(SerializeError::HeaderValue), result);
+    assert_eq!(b"previous", &out[..]);
+}
+
+#[test]
+fn write() {
+    let mut out = io::Cursor::new(Vec::new());
+    write_request(&mut out, b"HEAD", b"/", 1, vec![(b"Host".to_vec(), b"x".to_vec())]).unwrap();
+    assert_eq!(&b"HEAD / HTTP/1.1\r\nHost: x\r\n\r\n"[..], &out.get_ref()[..]);
+
+    let err = write_request(&mut out, b"HEAD", b"/\r\n", 1, Vec::<(&str, &str)>::new())
+        .unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+}
+
+#[test]
+fn response_head() {
+    let mut out = Vec::new();
+    encode_response(&mut out, 1, 404, None, vec![("Content-Length", "0")]).unwrap();
+    assert_eq!(&b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n"[..], &out[..]);
+
+    out.clear();
+    encode_response(&mut out, 0, 200, Some(&b"Fine"[..]), Vec::<(&str, &str)>::new()).unwrap();
+    assert_eq!(&b"HTTP/1.0 200 Fine\r\n\r\n"[..], &out[..]);
+
+    out.clear();
+    encode_response(&mut out, 1, 599, None, Vec::<(&str, &str)>::new()).unwrap();
+    assert_eq!(&b"HTTP/1.1 599 \r\n\r\n"[..], &out[..]);
+
+    let mut out = io::Cursor::new(Vec::new());
+    write_response(&mut out, 1, 101, None, vec![("Upgrade", "websocket")]).unwrap();
+    assert_eq!(&b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n"[..],
+               &out.get_ref()[..]);
+}
+
+#[test]
+fn reason_phrases() {
+    assert_eq!(Some("OK"), reason_phrase(200));
+    assert_eq!(Some("Request Header Fields Too Large"), reason_phrase(431));
+    assert_eq!(None, reason_phrase(299));
+}
+
+#[test]
+fn response_rejects_injection() {
+    let no_headers = Vec::<(&str, &str)>::new;
+    let mut out = Vec::new();
+    assert_eq!(Err(SerializeError::StatusCode),
+               encode_response(&mut out, 1, 99, None, no_headers()));
+    assert_eq!(Err(SerializeError::StatusCode),
+               encode_response(&mut out, 1, 1000, None, no_headers()));
+    assert_eq!(Err(SerializeError::Version),
+               encode_response(&mut out, 11, 200, None, no_headers()));
+    assert_eq!(Err(SerializeError::Reason),
+               encode_response(&mut out, 1, 200, Some(&b"OK\r\nSet-Cookie: a=b"[..]), no_headers()));
+    assert_eq!(Err(SerializeError::Reason),
+               encode_response(&mut out, 1, 200, Some(&b" OK"[..]), no_headers()));
+    assert_eq!(Err(SerializeError::HeaderValue),
+               encode_response(&mut out, 1, 200, None, vec![("A", "\r\n\r\n<html>")]));
+    assert!(out.is_empty());
+}
+
+// A small xorshift generator so the round-trip tests are reproducible
+// without pulling in a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, alphabet: &[u8], min: usize, max: usize) -> Vec<u8> {
+        let len = min + self.below(max - min + 1);
+        (0..len).map(|_| alphabet[self.below(alphabet.len())]).collect()
+    }
+
+    fn token(&mut self) -> Vec<u8> {
+        self.bytes(b"!#$%&'*+-.^_`|~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz",
+                   1,
+                   12)
+    }
+
+    // Field text without leading or trailing whitespace, including tabs and
+    // obs-text in the middle.
+    fn text(&mut self) -> Vec<u8> {
+        let mut alphabet: Vec<u8> = (0x21..0x7f).collect();
+        alphabet.extend_from_slice(b" \t\x80\xff");
+        let mut text = self.bytes(&alphabet, 0, 20);
+        while let Some(&b' ') | Some(&b'\t') = text.first() {
+            text.remove(0);
+        }
+        while let Some(&b' ') | Some(&b'\t') = text.last() {
+            text.pop();
+        }
+        text
+    }
+
+    fn headers(&mut self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        (0..self.below(8)).map(|_| (self.token(), self.text())).collect()
+    }
+}
+
+fn parsed_headers(headers: Headers) -> Vec<(Vec<u8>, Vec<u8>)> {
+    headers.iter().map(|h| (h.name().to_vec(), h.value().to_vec())).collect()
+}
+
+#[test]
+fn request_round_trip_property() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut target_alphabet: Vec<u8> = (0x21..0x7f).collect();
+    target_alphabet.push(0xe9);
+    for _ in 0..2000 {
+        let method = rng.token();
+        let target = rng.bytes(&target_alphabet, 1, 40);
+        let minor_version = rng.below(10) as u8;
+        let headers = rng.headers();
+
+        let mut out = Vec::new();
+        encode_request(&mut out, &method, &target, minor_version, headers.iter().cloned()).unwrap();
+
+        let mut storage = [phr_header::default(); 8];
+        match Request::parse(&out, &mut storage).unwrap() {
+            Status::Complete(consumed, req) => {
+                assert_eq!(out.len(), consumed);
+                assert_eq!(&method[..], req.method_bytes());
+                assert_eq!(&target[..], req.path());
+                assert_eq!(minor_version, req.minor_version());
+                assert_eq!(headers, parsed_headers(req.headers()));
+            }
+            Status::Partial => panic!("partial: {:?}", out),
+        }
+    }
+}
+
+#[test]
+fn response_round_trip_property() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..2000 {
+        let minor_version = rng.below(10) as u8;
+        let status = 100 + rng.below(900) as u16;
+        let reason = if rng.below(4) == 0 {
+            None
+        } else {
+            Some(rng.text())
+        };
+        let headers = rng.headers();
+
+        let mut out = Vec::new();
+        encode_response(&mut out,
+                        minor_version,
+                        status,
+                        reason.as_ref().map(|r| &r[..]),
+                        headers.iter().cloned())
+            .unwrap();
+
+        let mut storage = [phr_header::default(); 8];
+        match Response::parse(&out, &mut storage).unwrap() {
+            Status::Complete(consumed, res) => {
+                assert_eq!(out.len(), consumed);
+                assert_eq!(minor_version, res.minor_version());
+                assert_eq!(status, res.status());
+                let expected = match reason {
+                    Some(ref reason) => &reason[..],
+                    None => reason_phrase(status).unwrap_or("").as_bytes(),
+                };
+                assert_eq!(expected, res.reason());
+                assert_eq!(headers, parsed_headers(res.headers()));
+            }
+            Status::Partial => panic!("partial: {:?}", out),
+        }
+    }
+}
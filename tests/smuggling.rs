@@ -0,0 +1,94 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::smuggling::{self, Violation};
+use picohttpparser_sys::*;
+
+fn check_request(buf: &[u8]) -> Vec<Violation> {
+    let mut headers = [phr_header::default(); 8];
+    match Request::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, req) => smuggling::check_request(&req),
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+fn check_response(buf: &[u8]) -> Vec<Violation> {
+    let mut headers = [phr_header::default(); 8];
+    match Response::parse(buf, &mut headers).unwrap() {
+        Status::Complete(_, res) => smuggling::check_response(&res),
+        Status::Partial => panic!("unexpected partial"),
+    }
+}
+
+#[test]
+fn clean() {
+    assert!(check_request(b"GET / HTTP/1.1\r\nHost: a\r\n\r\n").is_empty());
+    assert!(check_request(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n").is_empty());
+    assert!(check_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n")
+        .is_empty());
+    assert!(check_request(b"GET / HTTP/1.1\r\nX-Folded: a\r\n b\r\n\r\n").is_empty());
+}
+
+#[test]
+fn content_length_with_transfer_encoding() {
+    assert_eq!(vec![Violation::ContentLengthWithTransferEncoding],
+               check_request(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\
+                               Transfer-Encoding: chunked\r\n\r\n"));
+}
+
+#[test]
+fn duplicate_content_length() {
+    assert_eq!(vec![Violation::DuplicateContentLength],
+               check_request(b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\n"));
+    assert_eq!(vec![Violation::DuplicateContentLength],
+               check_request(b"POST / HTTP/1.1\r\nContent-Length: 5, 6\r\n\r\n"));
+}
+
+#[test]
+fn invalid_content_length() {
+    assert_eq!(vec![Violation::InvalidContentLength],
+               check_request(b"POST / HTTP/1.1\r\nContent-Length: 0x10\r\n\r\n"));
+    assert_eq!(vec![Violation::InvalidContentLength],
+               check_request(b"POST / HTTP/1.1\r\nContent-Length: \r\n\r\n"));
+}
+
+#[test]
+fn chunked_not_final() {
+    assert_eq!(vec![Violation::ChunkedNotFinal],
+               check_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n"));
+    assert_eq!(vec![Violation::ChunkedNotFinal],
+               check_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: xchunked\r\n\r\n"));
+    assert!(check_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\n").is_empty());
+}
+
+#[test]
+fn chunked_repeated() {
+    assert_eq!(vec![Violation::ChunkedRepeated],
+               check_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\
+                               Transfer-Encoding: chunked\r\n\r\n"));
+}
+
+#[test]
+fn transfer_encoding_in_http10() {
+    assert_eq!(vec![Violation::TransferEncodingInHttp10],
+               check_request(b"POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n"));
+}
+
+#[test]
+fn folded_framing_header() {
+    assert_eq!(vec![Violation::FoldedFramingHeader, Violation::ChunkedNotFinal],
+               check_request(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n , identity\r\n\r\n"));
+}
+
+#[test]
+fn whitespace_before_header_name() {
+    assert_eq!(vec![Violation::WhitespaceBeforeHeaderName],
+               check_request(b"POST / HTTP/1.1\r\nHost: a\r\n Transfer-Encoding: chunked\r\n\r\n"));
+    assert_eq!(vec![Violation::WhitespaceBeforeHeaderName],
+               check_response(b"HTTP/1.1 200 OK\r\nX: a\r\n\tContent-Length: 5\r\n\r\n"));
+}
+
+#[test]
+fn display() {
+    assert_eq!("duplicate Content-Length",
+               Violation::DuplicateContentLength.to_string());
+}
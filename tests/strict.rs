@@ -0,0 +1,93 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+fn strict() -> ParserConfig {
+    let mut config = ParserConfig::new();
+    config.strict(true);
+    config
+}
+
+fn request(config: &ParserConfig, buf: &[u8]) -> Result<bool, ParseError> {
+    let mut headers = [phr_header::default(); 8];
+    config.parse_request(buf, &mut headers).map(|status| status.is_complete())
+}
+
+#[test]
+fn accepts_conforming_messages() {
+    let config = strict();
+    assert_eq!(Ok(true),
+               request(&config, b"GET /a?b=c HTTP/1.1\r\nHost: x\r\nX-Empty:\r\n\r\nbody\n"));
+    assert_eq!(Ok(true), request(&config, b"\r\nGET * HTTP/1.1\r\n\r\n"));
+    assert_eq!(Ok(false), request(&config, b"GET / HTTP/1.1\r\nHost: x"));
+
+    let mut headers = [phr_header::default(); 8];
+    let status = config.parse_response(b"HTTP/1.1 200 OK \xe9\r\nA: b\r\n\r\n", &mut headers);
+    assert!(status.unwrap().is_complete());
+}
+
+#[test]
+fn lenient_by_default() {
+    let config = ParserConfig::new();
+    assert_eq!(Ok(true), request(&config, b"GET / HTTP/1.1\nHost: x\n\n"));
+    assert_eq!(Ok(true), request(&config, b"GET / HTTP/1.1\r\nA: b\r\n c\r\n\r\n"));
+    assert_eq!(Ok(true), request(&config, b"G\"T /\xe9 HTTP/1.1\r\n\r\n"));
+}
+
+#[test]
+fn bare_lf() {
+    let config = strict();
+    assert_eq!(Err(ParseError::BareLf), request(&config, b"GET / HTTP/1.1\nHost: x\r\n\r\n"));
+    assert_eq!(Err(ParseError::BareLf), request(&config, b"GET / HTTP/1.1\r\nHost: x\n\r\n"));
+    assert_eq!(Err(ParseError::BareLf), request(&config, b"GET / HTTP/1.1\r\nHost: x\r\n\n"));
+    assert_eq!(Err(ParseError::BareLf), request(&config, b"\nGET / HTTP/1.1\r\n\r\n"));
+    // Rejected before the head is complete.
+    assert_eq!(Err(ParseError::BareLf), request(&config, b"GET / HTTP/1.1\nHo"));
+
+    let mut headers = [phr_header::default(); 8];
+    assert_eq!(Err(ParseError::BareLf),
+               config.parse_response(b"HTTP/1.1 200 OK\n\r\n", &mut headers).map(|_| ()));
+    assert_eq!(Err(ParseError::BareLf),
+               config.parse_headers(b"A: b\r\n\n", &mut headers).map(|_| ()));
+}
+
+#[test]
+fn obs_fold() {
+    let config = strict();
+    assert_eq!(Err(ParseError::ObsFold),
+               request(&config, b"GET / HTTP/1.1\r\nA: b\r\n c\r\n\r\n"));
+    assert_eq!(Err(ParseError::ObsFold),
+               request(&config, b"GET / HTTP/1.1\r\nA: b\r\n\tc\r\n\r\n"));
+}
+
+#[test]
+fn whitespace_before_colon() {
+    let config = strict();
+    assert_eq!(Err(ParseError::WhitespaceBeforeColon),
+               request(&config, b"GET / HTTP/1.0\r\nfoo : ab\r\n\r\n"));
+    assert_eq!(Err(ParseError::WhitespaceBeforeColon),
+               request(&config, b"GET / HTTP/1.0\r\nfoo\t: ab\r\n\r\n"));
+    assert_eq!(Err(ParseError::Malformed),
+               request(&ParserConfig::new(), b"GET / HTTP/1.0\r\nfoo : ab\r\n\r\n"));
+}
+
+#[test]
+fn invalid_tokens() {
+    let config = strict();
+    assert_eq!(Err(ParseError::InvalidMethod), request(&config, b"G\"T / HTTP/1.1\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidMethod), request(&config, b"GE(T / HTTP/1.1\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidMethod), request(&config, b" / HTTP/1.1\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidHeaderName),
+               request(&config, b"GET / HTTP/1.1\r\nA(b: c\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidHeaderName),
+               request(&config, b"GET / HTTP/1.1\r\nA\xe9: c\r\n\r\n"));
+}
+
+#[test]
+fn invalid_target() {
+    let config = strict();
+    assert_eq!(Err(ParseError::InvalidTarget), request(&config, b"GET /\xe9 HTTP/1.1\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidTarget),
+               request(&config, b"GET /a\x7fb HTTP/1.1\r\n\r\n"));
+    assert_eq!(Err(ParseError::InvalidTarget), request(&config, b"GET  HTTP/1.1\r\n\r\n"));
+}
@@ -0,0 +1,99 @@
+extern crate picohttpparser_sys;
+
+use picohttpparser_sys::*;
+
+#[test]
+fn origin_form() {
+    assert_eq!(Ok(RequestTarget::Origin {
+                   path: b"/a/b",
+                   query: Some(b"x=1&y"),
+               }),
+               RequestTarget::parse(b"/a/b?x=1&y", Method::Get));
+    assert_eq!(Ok(RequestTarget::Origin {
+                   path: b"/",
+                   query: None,
+               }),
+               RequestTarget::parse(b"/", Method::Post));
+    assert_eq!(Some(&b""[..]), RequestTarget::parse(b"/?", Method::Get).unwrap().query());
+}
+
+#[test]
+fn absolute_form() {
+    let target = RequestTarget::parse(b"http://user@example.com:8080/x?y=z", Method::Get).unwrap();
+    match target {
+        RequestTarget::Absolute { scheme, authority, path, query } => {
+            assert_eq!(b"http", scheme);
+            assert_eq!(b"user@example.com:8080", authority.as_bytes());
+            assert_eq!(Some(&b"user"[..]), authority.userinfo());
+            assert_eq!(b"example.com", authority.host());
+            assert_eq!(Some(8080), authority.port());
+            assert_eq!(b"/x", path);
+            assert_eq!(Some(&b"y=z"[..]), query);
+        }
+        target => panic!("unexpected {:?}", target),
+    }
+
+    let target = RequestTarget::parse(b"https://[::1]?q", Method::Get).unwrap();
+    assert_eq!(b"[::1]", target.authority().unwrap().host());
+    assert_eq!(None, target.authority().unwrap().port());
+    assert_eq!(b"", target.path());
+    assert_eq!(Some(&b"q"[..]), target.query());
+
+    let target = RequestTarget::parse(b"HTTP://host:/", Method::Get).unwrap();
+    assert_eq!(None, target.authority().unwrap().port());
+}
+
+#[test]
+fn authority_form() {
+    let target = RequestTarget::parse(b"example.com:443", Method::Connect).unwrap();
+    match target {
+        RequestTarget::Authority(authority) => {
+            assert_eq!(b"example.com", authority.host());
+            assert_eq!(Some(443), authority.port());
+            assert_eq!(None, authority.userinfo());
+        }
+        target => panic!("unexpected {:?}", target),
+    }
+    assert_eq!(Some(443), RequestTarget::parse(b"[2001:db8::1]:443", Method::Connect)
+                   .unwrap()
+                   .authority()
+                   .unwrap()
+                   .port());
+
+    for bad in &[&b"example.com"[..], b"example.com:", b":443", b"u@example.com:443",
+                 b"example.com:65536", b"example.com:44a", b"/", b"http://example.com:443"] {
+        assert_eq!(Err(ParseError::InvalidTarget),
+                   RequestTarget::parse(bad, Method::Connect),
+                   "{:?}",
+                   String::from_utf8_lossy(bad));
+    }
+}
+
+#[test]
+fn asterisk_form() {
+    assert_eq!(Ok(RequestTarget::Asterisk), RequestTarget::parse(b"*", Method::Options));
+    assert_eq!(Err(ParseError::InvalidTarget), RequestTarget::parse(b"*", Method::Get));
+}
+
+#[test]
+fn invalid() {
+    for bad in &[&b""[..], b"example.com", b"example.com:80", b"1http://x/", b"mailto:a@b",
+                 b"http://a b/", b"http://[::1/", b"http://[::1]x/", b"/a#frag",
+                 b"http:///x", b"http://", b"http://:80/", b"http://user@/"] {
+        assert_eq!(Err(ParseError::InvalidTarget),
+                   RequestTarget::parse(bad, Method::Get),
+                   "{:?}",
+                   String::from_utf8_lossy(bad));
+    }
+}
+
+#[test]
+fn from_request() {
+    let mut headers = [phr_header::default(); 4];
+    match Request::parse(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n", &mut headers).unwrap() {
+        Status::Complete(_, req) => {
+            assert_eq!(Some(443), req.target().unwrap().authority().unwrap().port());
+        }
+        Status::Partial => panic!("partial"),
+    }
+}